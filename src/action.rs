@@ -2,14 +2,22 @@ use super::*;
 
 #[derive(Debug)]
 pub(crate) enum Action {
-  Command(&'static str),
+  Command(&'static Invocation),
   Remove(&'static str),
 }
 
 impl Display for Action {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     match self {
-      Self::Command(cmd) => write!(f, "run `{cmd}`"),
+      Self::Command(invocation) => {
+        write!(f, "run `{invocation}`")?;
+
+        if let Some(tool) = &invocation.tool {
+          write!(f, " if `{tool}` is installed")?;
+        }
+
+        Ok(())
+      }
       Self::Remove(pattern) => write!(f, "remove {pattern}"),
     }
   }
@@ -29,9 +37,28 @@ impl TryFrom<ConfigAction> for Action {
 
         Ok(Action::Remove(Box::leak(remove.into_boxed_str())))
       }
-      ConfigAction::Command { command } => {
-        ensure!(!command.trim().is_empty(), "command action cannot be empty");
-        Ok(Action::Command(Box::leak(command.into_boxed_str())))
+      ConfigAction::Command(invocation) => {
+        ensure!(
+          !invocation.command.trim().is_empty(),
+          "command action cannot be empty"
+        );
+
+        ensure!(
+          invocation.shell.as_ref().is_none_or(|shell| shell
+            .first()
+            .is_some_and(|program| !program.is_empty())),
+          "command shell cannot be empty"
+        );
+
+        ensure!(
+          invocation
+            .tool
+            .as_ref()
+            .is_none_or(|tool| !tool.trim().is_empty()),
+          "command tool cannot be empty"
+        );
+
+        Ok(Action::Command(Box::leak(Box::new(invocation))))
       }
     }
   }
@@ -1,41 +1,171 @@
 use super::*;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+const ISO_DATE_UNITS: &[(char, u64)] = &[
+  ('Y', 60 * 60 * 24 * 365),
+  ('M', 60 * 60 * 24 * 30),
+  ('W', 60 * 60 * 24 * 7),
+  ('D', 60 * 60 * 24),
+];
+
+const ISO_TIME_UNITS: &[(char, u64)] = &[('H', 60 * 60), ('M', 60), ('S', 1)];
+
+const UNITS: &[(&str, u64)] = &[
+  ("y", 60 * 60 * 24 * 365),
+  ("mo", 60 * 60 * 24 * 30),
+  ("w", 60 * 60 * 24 * 7),
+  ("d", 60 * 60 * 24),
+  ("h", 60 * 60),
+  ("m", 60),
+  ("s", 1),
+];
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
 pub(crate) struct Age(pub(crate) Duration);
 
+impl Display for Age {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    if self.0.subsec_nanos() != 0 {
+      return write!(f, "{}ms", self.0.as_millis());
+    }
+
+    let seconds = self.0.as_secs();
+
+    let (unit, size) = UNITS
+      .iter()
+      .find(|(_, size)| seconds != 0 && seconds.is_multiple_of(*size))
+      .unwrap_or(&("s", 1));
+
+    write!(f, "{}{unit}", seconds / size)
+  }
+}
+
+impl From<Age> for String {
+  fn from(age: Age) -> Self {
+    age.to_string()
+  }
+}
+
 impl FromStr for Age {
   type Err = Error;
 
+  /// Parses an age like `1w3d`, `1.5d` or `P2W`.
   fn from_str(text: &str) -> Result<Self, Self::Err> {
     let text = text.trim().trim_end_matches("ago").trim();
 
-    let digits = text
-      .chars()
-      .take_while(char::is_ascii_digit)
-      .collect::<String>();
-
-    let suffix = text.chars().skip(digits.len()).collect::<String>();
-
-    let amount = digits
-      .parse::<u64>()
-      .map_err(|_| anyhow!("invalid age amount: `{digits}`"))?;
-
-    let seconds = match suffix.as_str() {
-      "s" => 1,
-      "m" => 60,
-      "h" => 60 * 60,
-      "d" => 60 * 60 * 24,
-      "w" => 60 * 60 * 24 * 7,
-      "mo" => 60 * 60 * 24 * 30,
-      "y" => 60 * 60 * 24 * 365,
-      _ => bail!("invalid age unit: `{suffix}`"),
-    };
+    if let Some(iso) = text.strip_prefix('P') {
+      return Self::iso(iso)
+        .ok_or_else(|| anyhow!("invalid ISO 8601 duration: `{text}`"));
+    }
+
+    let mut seconds = 0.0;
+
+    let mut rest = text;
+
+    loop {
+      let (amount, suffix) = Self::amount(rest);
+
+      let amount = amount
+        .parse::<f64>()
+        .map_err(|_| anyhow!("invalid age amount: `{amount}`"))?;
+
+      let suffix = suffix.trim_start();
+
+      let unit = suffix
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+
+      if unit == "ms" {
+        seconds += amount / 1000.0;
+      } else {
+        let Some((_, size)) = UNITS.iter().find(|(name, _)| *name == unit)
+        else {
+          bail!("invalid age unit: `{unit}`");
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        {
+          seconds += amount * *size as f64;
+        }
+      }
 
-    Ok(Age(Duration::from_secs(amount * seconds)))
+      rest = suffix[unit.len()..].trim_start();
+
+      if rest.is_empty() {
+        break;
+      }
+    }
+
+    Duration::try_from_secs_f64(seconds)
+      .map(Age)
+      .map_err(|_| anyhow!("age out of range: `{text}`"))
+  }
+}
+
+impl TryFrom<String> for Age {
+  type Error = Error;
+
+  fn try_from(text: String) -> Result<Self> {
+    text.parse()
   }
 }
 
 impl Age {
+  /// Splits the leading amount, digits and a decimal point, off `text`.
+  fn amount(text: &str) -> (&str, &str) {
+    text.split_at(
+      text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len()),
+    )
+  }
+
+  /// Parses the part of an ISO 8601 duration after the leading `P`.
+  fn iso(text: &str) -> Option<Self> {
+    let (date, time) = match text.split_once('T') {
+      Some((_, "")) => return None,
+      Some((date, time)) => (date, time),
+      None => (text, ""),
+    };
+
+    let mut seconds = 0.0;
+
+    let mut components = 0;
+
+    for (mut rest, units) in [(date, ISO_DATE_UNITS), (time, ISO_TIME_UNITS)] {
+      let mut allowed = units;
+
+      while !rest.is_empty() {
+        let (amount, suffix) = Self::amount(rest);
+
+        let amount = amount.parse::<f64>().ok()?;
+
+        let designator = suffix.chars().next()?;
+
+        let position =
+          allowed.iter().position(|(unit, _)| *unit == designator)?;
+
+        #[allow(clippy::cast_precision_loss)]
+        {
+          seconds += amount * allowed[position].1 as f64;
+        }
+
+        allowed = &allowed[position + 1..];
+
+        components += 1;
+
+        rest = &suffix[designator.len_utf8()..];
+      }
+    }
+
+    if components == 0 {
+      return None;
+    }
+
+    Duration::try_from_secs_f64(seconds).ok().map(Age)
+  }
+
   pub(crate) fn older_than(&self, modified: SystemTime) -> bool {
     let Ok(elapsed) = modified.elapsed() else {
       return false;
@@ -88,6 +218,54 @@ mod tests {
     case("  5d ago  ", 432_000);
   }
 
+  #[test]
+  fn parsing_compound_and_fractional() {
+    #[track_caller]
+    fn case(text: &str, expected_secs: u64) {
+      assert_eq!(
+        text.parse::<Age>().unwrap(),
+        Age(Duration::from_secs(expected_secs)),
+      );
+    }
+
+    case("1w3d", 864_000);
+    case("1d 12h", 129_600);
+    case("1.5d", 129_600);
+    case("0.5h", 1800);
+    case("2 d", 172_800);
+    case("1h30m ago", 5400);
+  }
+
+  #[test]
+  fn parsing_iso_8601() {
+    #[track_caller]
+    fn case(text: &str, expected_secs: u64) {
+      assert_eq!(
+        text.parse::<Age>().unwrap(),
+        Age(Duration::from_secs(expected_secs)),
+      );
+    }
+
+    case("P2W", 1_209_600);
+    case("P1D", 86400);
+    case("PT36H", 129_600);
+    case("P1DT12H", 129_600);
+    case("PT1M", 60);
+    case("P1M", 2_592_000);
+    case("P1Y2M", 36_720_000);
+    case("P0.5D", 43200);
+  }
+
+  #[test]
+  fn parse_invalid_iso_8601() {
+    for text in ["P", "PT", "P1DT", "P1H", "P1D1Y", "P1X"] {
+      assert_eq!(
+        text.parse::<Age>().unwrap_err().to_string(),
+        format!("invalid ISO 8601 duration: `{text}`"),
+      );
+    }
+  }
+
   #[test]
   fn parse_invalid_unit() {
     assert_eq!(
@@ -102,14 +280,41 @@ mod tests {
       "abcd".parse::<Age>().unwrap_err().to_string(),
       "invalid age amount: ``"
     );
+
+    assert_eq!(
+      "1.2.3d".parse::<Age>().unwrap_err().to_string(),
+      "invalid age amount: `1.2.3`"
+    );
+
+    assert_eq!(
+      "1d2".parse::<Age>().unwrap_err().to_string(),
+      "invalid age unit: ``"
+    );
+  }
+
+  #[test]
+  fn display_uses_largest_whole_unit() {
+    #[track_caller]
+    fn case(text: &str, expected: &str) {
+      assert_eq!(text.parse::<Age>().unwrap().to_string(), expected);
+    }
+
+    case("0s", "0s");
+    case("90s", "90s");
+    case("120s", "2m");
+    case("7d", "1w");
+    case("30d", "1mo");
+    case("2y", "2y");
+    case("0.5s", "500ms");
+    case("1500ms", "1500ms");
   }
 
   #[test]
   fn older_than() {
     let now = SystemTime::now();
-    let age = Age(Duration::from_secs(60));
+    let age = Age(Duration::from_mins(1));
 
     assert!(!age.older_than(now));
-    assert!(age.older_than(now - Duration::from_secs(120)));
+    assert!(age.older_than(now - Duration::from_mins(2)));
   }
 }
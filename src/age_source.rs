@@ -0,0 +1,13 @@
+use super::*;
+
+/// What a project's age is measured from.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum AgeSource {
+  Accessed,
+  Commit,
+  #[default]
+  Files,
+}
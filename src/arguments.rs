@@ -8,12 +8,53 @@ use super::*;
   about = "A configurable project cleaning tool"
 )]
 pub(crate) struct Arguments {
+  #[clap(
+    long,
+    value_enum,
+    value_name = "SOURCE",
+    help = "What to measure project age from [default: files]"
+  )]
+  age_source: Option<AgeSource>,
+  #[clap(long, help = "Allow removing paths that are tracked by git")]
+  allow_tracked: bool,
+  #[clap(
+    long,
+    help = "Report file lengths instead of the disk space paths take up"
+  )]
+  apparent_size: bool,
+  #[clap(
+    long,
+    global = true,
+    value_name = "PATH",
+    help = "Load configuration from PATH instead of the default config file, \
+            also read from SWAB_CONFIG"
+  )]
+  config: Option<PathBuf>,
   #[arg(help = "Directories to scan for projects to clean")]
   directories: Vec<PathBuf>,
   #[clap(long, help = "Enable dry run mode")]
   dry_run: bool,
+  #[clap(
+    long,
+    value_name = "GLOB",
+    help = "Never touch paths matching GLOB, may be given multiple times"
+  )]
+  exclude: Vec<String>,
   #[clap(long, help = "Follow symlinks during traversal")]
   follow_symlinks: bool,
+  #[clap(
+    long,
+    value_enum,
+    default_value_t = Format::Text,
+    help = "Output format"
+  )]
+  format: Format,
+  #[clap(
+    long,
+    value_name = "SIZE",
+    help = "Clean the fewest, stalest projects needed to get SIZE of free space"
+  )]
+  free: Option<Bytes>,
   #[clap(
     short,
     long,
@@ -21,12 +62,51 @@ pub(crate) struct Arguments {
     conflicts_with = "quiet"
   )]
   interactive: bool,
+  #[clap(
+    short,
+    long,
+    value_name = "N",
+    help = "Number of worker threads to use [default: number of CPUs]"
+  )]
+  jobs: Option<NonZeroUsize>,
+  #[clap(
+    long,
+    help = "Keep cleaning other projects when a task fails, and report failures at the end"
+  )]
+  keep_going: bool,
+  #[clap(
+    long,
+    value_name = "DEPTH",
+    help = "Maximum directory depth to search for projects"
+  )]
+  max_depth: Option<usize>,
+  #[clap(
+    long,
+    value_name = "SIZE",
+    help = "Skip paths and projects smaller than SIZE (e.g., 100MiB, 1.5 GiB)"
+  )]
+  min_size: Option<Bytes>,
+  #[clap(long, help = "Keep searching for projects inside detected projects")]
+  nested: bool,
+  #[clap(
+    long,
+    value_name = "TIME",
+    help = "Only clean projects modified after the specified age or date (e.g., 1w, 2026-01-01)"
+  )]
+  newer_than: Option<Cutoff>,
+  #[clap(
+    long,
+    global = true,
+    help = "Ignore config files and use only the built-in rules",
+    conflicts_with = "config"
+  )]
+  no_config: bool,
   #[clap(
     long,
-    value_name = "AGE",
-    help = "Only clean projects older than the specified age (e.g., 30d, 2w, 1mo)"
+    value_name = "TIME",
+    help = "Only clean projects older than the specified age or date (e.g., 30d, 1w3d, P2W, 2026-01-01)"
   )]
-  older_than: Option<Age>,
+  older_than: Option<Cutoff>,
   #[clap(
     short,
     long,
@@ -34,94 +114,401 @@ pub(crate) struct Arguments {
     conflicts_with = "interactive"
   )]
   quiet: bool,
+  #[clap(
+    long,
+    conflicts_with_all = ["interactive", "quiet"],
+    help = "Pick what to clean from a full-screen list of every matched project and task"
+  )]
+  select: bool,
+  #[command(flatten)]
+  selection: Selection,
+  #[clap(
+    long,
+    help = "Skip projects that a running process is using (Linux only)"
+  )]
+  skip_active: bool,
+  #[clap(long, help = "Skip projects with uncommitted git changes")]
+  skip_dirty: bool,
+  #[clap(long, value_enum, help = "Order projects in the output")]
+  sort: Option<Sort>,
   #[clap(subcommand)]
   subcommand: Option<Subcommand>,
+  #[clap(long, value_name = "N", help = "Only clean the N largest projects")]
+  top: Option<usize>,
+  #[clap(
+    long,
+    help = "Move matched paths to the trash instead of deleting them"
+  )]
+  trash: bool,
+  #[clap(long, help = "Print the output of commands that succeed")]
+  verbose: bool,
 }
 
 impl Arguments {
-  fn print_summary(&self, total_projects: u64, total_bytes: u64) {
-    if self.quiet {
-      return;
+  fn clean(
+    &self,
+    rules: &[Box<dyn Rule>],
+    age_source: AgeSource,
+    exclude: Exclude,
+    trash: Option<&Trash>,
+  ) -> Result {
+    let directories = if self.directories.is_empty() {
+      vec![env::current_dir()?]
+    } else {
+      self.directories.clone()
+    };
+
+    directories.iter().try_for_each(|root| {
+      ensure!(
+        root.is_dir(),
+        "the path `{}` is not a valid directory",
+        root.display()
+      );
+
+      Ok(())
+    })?;
+
+    let indexes = directories
+      .into_iter()
+      .map(|root| {
+        Index::new(
+          root,
+          age_source,
+          self.follow_symlinks,
+          self.max_depth,
+          rules,
+        )
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    let discovery = Discovery {
+      max_depth: self.max_depth,
+      nested: self.nested,
+    };
+
+    let contexts = indexes.iter().try_fold(
+      Vec::new(),
+      |mut acc: Vec<Context>, index| -> Result<Vec<Context>> {
+        acc.extend(discovery.run(index, rules)?);
+        Ok(acc)
+      },
+    )?;
+
+    let guard = Guard {
+      allow_tracked: self.allow_tracked,
+      exclude,
+      min_size: self.min_size,
+      newer_than: self.newer_than,
+      older_than: self.older_than,
+      processes: self.skip_active.then(Processes::scan).transpose()?,
+      repositories: Repositories::default(),
+      skip_dirty: self.skip_dirty,
+    };
+
+    let usage = Usage::new(self.apparent_size, &guard.exclude);
+
+    let mut tally = Tally::default();
+
+    let mut projects = Vec::new();
+
+    for (root, result) in contexts
+      .into_par_iter()
+      .map(|context| {
+        (
+          context.root.clone(),
+          Project::new(context, rules, &guard, &usage),
+        )
+      })
+      .collect::<Vec<_>>()
+    {
+      match result {
+        Ok(project) => projects.push(project),
+        Err(error) if self.keep_going => tally.failures.push(Failure {
+          error,
+          root,
+          rule_id: None,
+        }),
+        Err(error) => return Err(error),
+      }
+    }
+
+    // Projects are measured concurrently, so a file hard-linked into several
+    // of them is credited to the first in order only once all are measured.
+    let mut seen = HashSet::new();
+
+    for project in &mut projects {
+      project.credit(rules, &guard, &usage, &mut seen);
+    }
+
+    if let Some(top) = self.top {
+      projects.retain(Project::cleanable);
+      Sort::Size.sort(&mut projects);
+      projects.truncate(top);
     }
 
-    let (projects_label, bytes_label) = if self.dry_run {
-      ("Projects matched", "Bytes matched")
+    let mut plans = Vec::new();
+
+    if let Some(free) = self.free {
+      (projects, plans) = Plan::make(projects, free)?;
+
+      if !self.quiet {
+        for plan in &plans {
+          match self.format {
+            Format::Json => {}
+            Format::Ndjson => {
+              println!("{}", serde_json::to_string(&Record::Plan(plan))?);
+            }
+            Format::Text => print!("{plan}"),
+          }
+        }
+      }
+    }
+
+    if let Some(sort) = self.sort {
+      sort.sort(&mut projects);
+    }
+
+    if self.select {
+      ensure!(
+        io::stdin().is_terminal() && io::stdout().is_terminal(),
+        "`--select` needs an interactive terminal"
+      );
+
+      let (cleanable, skipped) = projects
+        .into_iter()
+        .partition::<Vec<_>, _>(Project::cleanable);
+
+      let Some(selected) =
+        Selector::run(cleanable, self.sort.unwrap_or(Sort::Size))?
+      else {
+        return Ok(());
+      };
+
+      projects = selected.into_iter().chain(skipped).collect();
+    }
+
+    let filesystems = if self.dry_run || trash.is_some() || cfg!(not(unix)) {
+      None
     } else {
-      ("Projects cleaned", "Bytes deleted")
+      Some(Filesystem::distinct(
+        projects
+          .iter()
+          .filter(|project| project.cleanable())
+          .map(|project| project.context.root.as_path()),
+      )?)
     };
 
-    let style = Style::stdout();
+    let tally = Mutex::new(tally);
+
+    rayon::scope(|scope| {
+      for project in &projects {
+        if self.stopped(&tally) {
+          break;
+        }
+
+        if self.keep_going {
+          self.process_context(project, trash, scope, &tally)?;
+        } else {
+          rayon::scope(|scope| {
+            self.process_context(project, trash, scope, &tally)
+          })?;
+        }
+      }
+
+      Ok::<_, Error>(())
+    })?;
+
+    let reclaimed = filesystems
+      .as_deref()
+      .map(Filesystem::reclaimed)
+      .transpose()?;
+
+    let tally = tally.into_inner().unwrap_or_else(PoisonError::into_inner);
+
+    let (bytes, projects_cleaned, mut failures) =
+      (tally.bytes(), tally.projects(), tally.failures);
 
-    println!(
-      "{}: {}, {}: {}",
-      style.apply(BOLD, projects_label),
-      style.apply(CYAN, total_projects),
-      style.apply(BOLD, bytes_label),
-      style.apply(GREEN, Bytes(total_bytes)),
+    if !self.keep_going && !failures.is_empty() {
+      return Err(failures.remove(0).error);
+    }
+
+    failures.sort_by(|a, b| a.root.cmp(&b.root));
+
+    let failed = failures.len();
+
+    self.print_summary(
+      &plans,
+      &projects,
+      &Summary {
+        bytes,
+        dry_run: self.dry_run,
+        failures,
+        projects: projects_cleaned,
+        reclaimed,
+        trash: trash.is_some(),
+      },
+    )?;
+
+    ensure!(
+      failed == 0,
+      "{failed} task{} failed",
+      if failed == 1 { "" } else { "s" }
     );
+
+    Ok(())
+  }
+
+  /// The config file passed with `--config` or set in `SWAB_CONFIG`, which
+  /// is read here rather than by clap so that it doesn't conflict with
+  /// `--no-config`.
+  fn config(&self) -> Option<PathBuf> {
+    self.config.clone().or_else(|| {
+      env::var_os("SWAB_CONFIG")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+    })
+  }
+
+  /// The config file passed with `--config`, or the default one.
+  pub(crate) fn config_path(&self) -> Result<PathBuf> {
+    match self.config() {
+      Some(path) => Ok(path),
+      None => Ok(confy::get_configuration_file_path("swab", "config")?),
+    }
+  }
+
+  fn failed(tally: &Mutex<Tally>) -> bool {
+    !tally
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .failures
+      .is_empty()
+  }
+
+  pub(crate) fn load_config(&self) -> Result<Config> {
+    if self.no_config {
+      return Ok(Config::default());
+    }
+
+    Config::load(self.config().as_deref())
+  }
+
+  fn print_report(&self, report: &Report) -> Result {
+    if self.quiet {
+      return Ok(());
+    }
+
+    match self.format {
+      Format::Json => {}
+      Format::Ndjson => {
+        println!("{}", serde_json::to_string(&Record::Project(report))?);
+      }
+      Format::Text => {
+        print!("{report}");
+        io::stdout().flush()?;
+      }
+    }
+
+    Ok(())
   }
 
-  fn process_context(
+  fn print_summary(
     &self,
-    context: &Context,
-    rules: &[Box<dyn Rule>],
-  ) -> Result<(u64, bool)> {
-    let mut seen_removals = HashSet::new();
+    plans: &[Plan],
+    projects: &[Project],
+    summary: &Summary,
+  ) -> Result {
+    if self.quiet {
+      return Ok(());
+    }
 
-    let reports = rules
-      .iter()
-      .filter(|rule| rule.detection().matches(context))
-      .map(|rule| context.report(rule.as_ref()))
-      .collect::<Result<Vec<_>>>()?;
+    match self.format {
+      Format::Json => {
+        let document = Document {
+          plans,
+          projects: projects
+            .iter()
+            .flat_map(|project| &project.reports)
+            .collect(),
+          summary,
+        };
 
-    let reports = reports
-      .into_iter()
-      .filter(|report| !report.tasks.is_empty())
-      .collect::<Vec<Report>>();
+        println!("{}", serde_json::to_string_pretty(&document)?);
+      }
+      Format::Ndjson => {
+        println!("{}", serde_json::to_string(&Record::Summary(summary))?);
+      }
+      Format::Text => print!("{summary}"),
+    }
 
-    let has_matches = !reports.is_empty();
+    Ok(())
+  }
 
-    let (bytes, executed) = reports.iter().try_fold(
-      (0u64, false),
-      |(bytes, executed), report| -> Result<_> {
-        if !self.quiet {
-          print!("{report}");
-          io::stdout().flush()?;
-        }
+  fn process_context<'scope>(
+    &self,
+    project: &'scope Project,
+    trash: Option<&'scope Trash>,
+    scope: &Scope<'scope>,
+    tally: &'scope Mutex<Tally>,
+  ) -> Result {
+    let mut seen_removals = HashSet::new();
 
-        report.tasks.iter().try_fold(
-          (bytes, executed),
-          |(bytes, executed), task| -> Result<_> {
-            let (task_bytes, task_executed) =
-              self.process_task(task, context, &mut seen_removals)?;
+    if project.reports.iter().any(|report| report.skip.is_some()) {
+      for report in &project.reports {
+        self.print_report(report)?;
+      }
 
-            Ok((bytes + task_bytes, executed || task_executed))
-          },
-        )
-      },
-    )?;
+      return Ok(());
+    }
+
+    for report in &project.reports {
+      self.print_report(report)?;
 
-    let should_count = if self.dry_run { has_matches } else { executed };
+      for task in &report.tasks {
+        if self.stopped(tally) {
+          return Ok(());
+        }
+
+        self.process_task(
+          task,
+          &project.context,
+          &report.rule_id,
+          &mut seen_removals,
+          trash,
+          scope,
+          tally,
+        )?;
+      }
+    }
 
-    Ok((bytes, should_count))
+    Ok(())
   }
 
-  fn process_task(
+  fn process_task<'scope>(
     &self,
-    task: &Task,
-    context: &Context,
+    task: &'scope Task,
+    context: &'scope Context,
+    rule_id: &'scope str,
     seen_removals: &mut HashSet<PathBuf>,
-  ) -> Result<(u64, bool)> {
+    trash: Option<&'scope Trash>,
+    scope: &Scope<'scope>,
+    tally: &'scope Mutex<Tally>,
+  ) -> Result {
     let (style, theme) = (Style::stdout(), ColorfulTheme::default());
 
     match task {
       Task::Remove { path, size } => {
         if !seen_removals.insert(path.clone()) {
-          return Ok((0, false));
+          return Ok(());
         }
 
         if self.dry_run {
-          return Ok((*size, false));
+          tally
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clean(&context.root, *size);
+
+          return Ok(());
         }
 
         let confirmation = Confirm::with_theme(&theme)
@@ -134,16 +521,51 @@ impl Arguments {
           .default(true);
 
         if self.interactive && !confirmation.interact()? {
-          return Ok((0, false));
+          return Ok(());
         }
 
-        task.execute(context)?;
-
-        Ok((*size, true))
+        let keep_going = self.keep_going;
+
+        scope.spawn(move |_| {
+          if !keep_going && Self::failed(tally) {
+            return;
+          }
+
+          let full_path = context.root.join(path);
+
+          let result = match trash {
+            Some(trash) => trash
+              .put(&full_path, &context.index.root, *size)
+              .with_context(|| {
+                format!("failed to move `{}` to the trash", full_path.display())
+              }),
+            None => task.execute(context).with_context(|| {
+              format!("failed to remove `{}`", full_path.display())
+            }),
+          };
+
+          let mut tally = tally.lock().unwrap_or_else(PoisonError::into_inner);
+
+          match result {
+            Ok(()) => tally.clean(&context.root, *size),
+            Err(error) => tally.failures.push(Failure {
+              error,
+              root: context.root.clone(),
+              rule_id: Some(rule_id.to_owned()),
+            }),
+          }
+        });
+
+        Ok(())
       }
       Task::Command(command) => {
         if self.dry_run {
-          return Ok((0, false));
+          tally
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clean(&context.root, 0);
+
+          return Ok(());
         }
 
         let confirmation = Confirm::with_theme(&theme)
@@ -155,12 +577,40 @@ impl Arguments {
           .default(true);
 
         if self.interactive && !confirmation.interact()? {
-          return Ok((0, false));
+          return Ok(());
+        }
+
+        let output = match command.run(&context.root) {
+          Ok(output) => output,
+          Err(error) if self.keep_going => {
+            tally
+              .lock()
+              .unwrap_or_else(PoisonError::into_inner)
+              .failures
+              .push(Failure {
+                error,
+                root: context.root.clone(),
+                rule_id: Some(rule_id.to_owned()),
+              });
+
+            return Ok(());
+          }
+          Err(error) => return Err(error),
+        };
+
+        if self.verbose
+          && !self.quiet
+          && let Some(output) = output.filter(|output| !output.is_empty())
+        {
+          eprint!("{output}");
         }
 
-        task.execute(context)?;
+        tally
+          .lock()
+          .unwrap_or_else(PoisonError::into_inner)
+          .clean(&context.root, 0);
 
-        Ok((0, true))
+        Ok(())
       }
     }
   }
@@ -169,75 +619,54 @@ impl Arguments {
     self.quiet
   }
 
-  pub(crate) fn run(self) -> Result {
-    if let Some(subcommand) = self.subcommand {
-      return subcommand.run();
+  pub(crate) fn run(mut self) -> Result {
+    if let Some(subcommand) = self.subcommand.take() {
+      return subcommand.run(&self);
     }
 
-    let rules: Vec<Box<dyn Rule>> = Config::load()?.try_into()?;
+    let config = self.load_config()?;
 
-    let directories = if self.directories.is_empty() {
-      vec![env::current_dir()?]
+    let trash = if self.trash {
+      let trash = config.trash.trash()?;
+
+      for error in trash.purge(config.trash.expire_after)?.1 {
+        eprintln!("warning: {error:#}");
+      }
+
+      Some(trash)
     } else {
-      self.directories.clone()
+      None
     };
 
-    directories.iter().try_for_each(|root| {
-      ensure!(
-        root.is_dir(),
-        "the path `{}` is not a valid directory",
-        root.display()
-      );
+    let exclude = Exclude::new(
+      &config
+        .exclude
+        .iter()
+        .chain(&self.exclude)
+        .collect::<Vec<_>>(),
+    )?;
 
-      Ok(())
-    })?;
+    let age_source = self.age_source.unwrap_or(config.age_source);
 
-    let directories = directories.iter().try_fold(
-      Vec::new(),
-      |mut acc: Vec<PathBuf>, root| -> Result<Vec<PathBuf>> {
-        acc.push(root.clone());
-        acc.extend(root.directories(self.follow_symlinks)?);
-        Ok(acc)
-      },
-    )?;
+    let mut rules: Vec<Box<dyn Rule>> = config.try_into()?;
 
-    let contexts = directories
-      .into_iter()
-      .map(|directory| Context::new(directory, self.follow_symlinks))
-      .collect::<Result<Vec<_>>>()?;
+    self.selection.check(&rules)?;
 
-    let contexts = contexts
-      .into_iter()
-      .filter(|context| {
-        let Some(age) = &self.older_than else {
-          return true;
-        };
+    rules.retain(|rule| self.selection.selects(rule.id(), rule.tags()));
 
-        context
-          .modified_time()
-          .map(|modified| age.older_than(modified))
-          .unwrap_or(false)
-      })
-      .collect::<Vec<_>>();
-
-    let (total_bytes, total_projects) = contexts.into_iter().try_fold(
-      (0u64, 0u64),
-      |totals @ (total_bytes, total_projects), context| {
-        self
-          .process_context(&context, &rules)
-          .map(|(bytes, should_count)| {
-            if should_count {
-              (total_bytes + bytes, total_projects + 1)
-            } else {
-              totals
-            }
-          })
-      },
-    )?;
+    let pool = ThreadPoolBuilder::new()
+      .num_threads(self.jobs.map_or(0, NonZeroUsize::get))
+      .build()?;
+
+    pool.install(|| self.clean(&rules, age_source, exclude, trash.as_ref()))
+  }
 
-    self.print_summary(total_projects, total_bytes);
+  pub(crate) fn selection(&self) -> &Selection {
+    &self.selection
+  }
 
-    Ok(())
+  fn stopped(&self, tally: &Mutex<Tally>) -> bool {
+    !self.keep_going && Self::failed(tally)
   }
 }
 
@@ -261,4 +690,36 @@ mod tests {
       Err(error) if error.kind() == ErrorKind::ArgumentConflict
     ));
   }
+
+  #[test]
+  fn jobs_must_be_positive() {
+    let result =
+      Arguments::command().try_get_matches_from(["swab", "--jobs", "0"]);
+
+    assert!(matches!(
+      result,
+      Err(error) if error.kind() == ErrorKind::ValueValidation
+    ));
+  }
+
+  #[test]
+  fn restore_requires_paths_or_all() {
+    let result = Arguments::command().try_get_matches_from(["swab", "restore"]);
+
+    assert!(matches!(
+      result,
+      Err(error) if error.kind() == ErrorKind::MissingRequiredArgument
+    ));
+  }
+
+  #[test]
+  fn restore_paths_and_all_conflict() {
+    let result = Arguments::command()
+      .try_get_matches_from(["swab", "restore", "--all", "foo"]);
+
+    assert!(matches!(
+      result,
+      Err(error) if error.kind() == ErrorKind::ArgumentConflict
+    ));
+  }
 }
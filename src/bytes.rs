@@ -39,10 +39,14 @@ impl FromStr for Bytes {
       matches!(c, '0'..='9' | '.')
     }
 
+    let text = text.trim();
+
     let digits = text.chars().take_while(is_digit).collect::<String>();
 
     let suffix = text.chars().skip_while(is_digit).collect::<String>();
 
+    let suffix = suffix.trim_start();
+
     let value = digits.parse::<f64>()?;
 
     let multiple = match suffix.to_lowercase().as_str() {
@@ -62,6 +66,16 @@ impl FromStr for Bytes {
   }
 }
 
+impl<'de> Deserialize<'de> for Bytes {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    String::deserialize(deserializer)?
+      .parse()
+      .map_err(de::Error::custom)
+  }
+}
+
 impl Display for Bytes {
   #![allow(clippy::float_cmp)]
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -93,6 +107,12 @@ impl Display for Bytes {
   }
 }
 
+impl Serialize for Bytes {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -142,6 +162,13 @@ mod tests {
     }
   }
 
+  #[test]
+  fn parse_displayed_sizes() {
+    for bytes in [Bytes(0), Bytes(1), Bytes(MI + 512 * KI), Bytes(3 * GI)] {
+      assert_eq!(bytes.to_string().parse::<Bytes>().unwrap(), bytes);
+    }
+  }
+
   #[test]
   fn parse_invalid_suffix() {
     assert_eq!(
@@ -6,13 +6,65 @@ pub(crate) struct DefaultRulesConfig {
   pub(crate) disabled: Vec<String>,
 }
 
+/// Settings read from a `.swab.toml` in a project root.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProjectConfig {
+  pub(crate) disabled: Vec<String>,
+  pub(crate) keep: Vec<String>,
+  pub(crate) remove: Vec<String>,
+  pub(crate) skip: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct TrashConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) directory: Option<PathBuf>,
+  pub(crate) expire_after: Age,
+}
+
+impl Default for TrashConfig {
+  fn default() -> Self {
+    Self {
+      directory: None,
+      expire_after: Age(Duration::from_hours(24 * 30)),
+    }
+  }
+}
+
+impl TrashConfig {
+  pub(crate) fn trash(&self) -> Result<Trash> {
+    if let Some(directory) = &self.directory {
+      ensure!(
+        directory.is_absolute(),
+        "trash directory `{}` must be an absolute path",
+        directory.display()
+      );
+    }
+
+    Ok(Trash::new(match &self.directory {
+      Some(directory) => directory.clone(),
+      None => Trash::default_directory()?,
+    }))
+  }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct RuleConfig {
   #[serde(default)]
   pub(crate) actions: Vec<ConfigAction>,
+  #[serde(default)]
+  pub(crate) allow_tracked: bool,
   pub(crate) detection: ConfigDetection,
   pub(crate) id: String,
+  #[serde(default)]
+  pub(crate) keep: Vec<String>,
+  pub(crate) min_size: Option<Bytes>,
   pub(crate) name: Option<String>,
+  pub(crate) older_than: Option<Cutoff>,
+  #[serde(default)]
+  pub(crate) tags: Vec<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -20,6 +72,8 @@ pub(crate) struct RuleConfig {
 pub(crate) enum ConfigDetection {
   All { all: Vec<ConfigDetection> },
   Any { any: Vec<ConfigDetection> },
+  Contains { contains: String, file: String },
+  Key { file: String, key: String },
   Not { not: Box<ConfigDetection> },
   Pattern(String),
   PatternMap { pattern: String },
@@ -50,9 +104,17 @@ impl Display for ConfigDetection {
             .join(" OR ")
         )
       }
+      Self::Contains { contains, file } => {
+        write!(f, "contains({file:?}, {contains:?})")
+      }
+      Self::Key { file, key } => write!(f, "key({file:?}, {key:?})"),
       Self::Not { not } => write!(f, "NOT {not}"),
-      Self::Pattern(pattern) | Self::PatternMap { pattern } => {
-        write!(f, "{pattern}")
+      Self::Pattern(expression) => match Expression::parse(expression) {
+        Ok(detection) => write!(f, "{detection}"),
+        Err(_) => write!(f, "{expression}"),
+      },
+      Self::PatternMap { pattern } => {
+        write!(f, "{}", Expression::quote(pattern))
       }
     }
   }
@@ -83,25 +145,38 @@ impl ConfigDetection {
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub(crate) enum ConfigAction {
-  Command { command: String },
+  Command(Invocation),
   Remove { remove: String },
 }
 
 impl Display for ConfigAction {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     match self {
-      Self::Command { command } => write!(f, "run `{command}`"),
+      Self::Command(invocation) => {
+        write!(f, "run `{invocation}`")?;
+
+        if let Some(tool) = &invocation.tool {
+          write!(f, " if `{tool}` is installed")?;
+        }
+
+        Ok(())
+      }
       Self::Remove { remove } => write!(f, "remove {remove}"),
     }
   }
 }
 
 #[derive(Debug)]
-struct CustomRule {
+pub(crate) struct CustomRule {
   actions: Vec<Action>,
+  allow_tracked: bool,
   detection: Detection,
   id: String,
+  keep: Vec<&'static str>,
+  min_size: Option<Bytes>,
   name: String,
+  older_than: Option<Cutoff>,
+  tags: Vec<&'static str>,
 }
 
 impl TryFrom<RuleConfig> for CustomRule {
@@ -118,11 +193,26 @@ impl TryFrom<RuleConfig> for CustomRule {
       .map(ConfigAction::try_into)
       .collect::<Result<Vec<_>>>()?;
 
+    Keep::new(&rule.keep)?;
+
     Ok(Self {
       actions,
+      allow_tracked: rule.allow_tracked,
       detection: rule.detection.try_into()?,
       id: rule.id.clone(),
+      keep: rule
+        .keep
+        .into_iter()
+        .map(|pattern| &*Box::leak(pattern.into_boxed_str()))
+        .collect(),
+      min_size: rule.min_size,
       name: rule.name.unwrap_or(rule.id),
+      older_than: rule.older_than,
+      tags: rule
+        .tags
+        .into_iter()
+        .map(|tag| &*Box::leak(tag.into_boxed_str()))
+        .collect(),
     })
   }
 }
@@ -132,6 +222,10 @@ impl Rule for CustomRule {
     &self.actions
   }
 
+  fn allow_tracked(&self) -> bool {
+    self.allow_tracked
+  }
+
   fn detection(&self) -> Detection {
     self.detection.clone()
   }
@@ -140,17 +234,36 @@ impl Rule for CustomRule {
     self.id.as_str()
   }
 
+  fn keep(&self) -> &[&str] {
+    &self.keep
+  }
+
+  fn min_size(&self) -> Option<Bytes> {
+    self.min_size
+  }
+
   fn name(&self) -> &str {
     self.name.as_str()
   }
+
+  fn older_than(&self) -> Option<Cutoff> {
+    self.older_than
+  }
+
+  fn tags(&self) -> &[&str] {
+    &self.tags
+  }
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub(crate) struct Config {
+  pub(crate) age_source: AgeSource,
   #[serde(alias = "default")]
   pub(crate) default_rules: DefaultRulesConfig,
+  pub(crate) exclude: Vec<String>,
   pub(crate) rules: Vec<RuleConfig>,
+  pub(crate) trash: TrashConfig,
 }
 
 impl TryInto<Vec<Box<dyn Rule>>> for Config {
@@ -214,7 +327,18 @@ impl Config {
       .copied()
   }
 
-  pub(crate) fn load() -> Result<Self> {
-    Ok(confy::load("swab", "config")?)
+  /// Loads the config at `path`, which must exist, or the default file.
+  pub(crate) fn load(path: Option<&Path>) -> Result<Self> {
+    let Some(path) = path else {
+      return Ok(confy::load("swab", "config")?);
+    };
+
+    let contents = fs::read_to_string(path).map_err(|error| {
+      anyhow!("failed to read config file `{}`: {error}", path.display())
+    })?;
+
+    toml::from_str(&contents).map_err(|error| {
+      anyhow!("invalid config file `{}`: {error}", path.display())
+    })
   }
 }
@@ -1,25 +1,39 @@
 use super::*;
 
 #[derive(Debug)]
-pub(crate) struct Context {
-  pub(crate) directories: HashSet<PathBuf>,
-  pub(crate) files: HashSet<PathBuf>,
+pub(crate) struct Context<'a> {
+  pub(crate) commit: OnceLock<Option<SystemTime>>,
+  pub(crate) detected: OnceLock<HashSet<usize>>,
   pub(crate) follow_symlinks: bool,
+  pub(crate) index: &'a Index,
+  pub(crate) local: Local,
+  pub(crate) manifests: Mutex<HashMap<&'static str, Option<Manifest>>>,
+  pub(crate) relative: PathBuf,
   pub(crate) root: PathBuf,
+  pub(crate) times: OnceLock<Vec<(PathBuf, SystemTime)>>,
 }
 
-impl Context {
+impl Context<'_> {
   pub(crate) fn contains(&self, pattern: &str) -> bool {
-    let matcher = match Glob::new(pattern) {
-      Ok(glob) => glob.compile_matcher(),
-      Err(_) => return false,
-    };
+    self.index.pattern(pattern).is_some_and(|pattern| {
+      self
+        .detected
+        .get_or_init(|| self.index.detected(&self.relative, &self.relative))
+        .contains(&pattern)
+    })
+  }
 
+  /// Whether the manifest `file` has the dotted `key`, loading each manifest
+  /// only once.
+  pub(crate) fn has_key(&self, file: &'static str, key: &str) -> bool {
     self
-      .directories
-      .iter()
-      .chain(self.files.iter())
-      .any(|path| matcher.is_match(path))
+      .manifests
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .entry(file)
+      .or_insert_with(|| Manifest::load(&self.root.join(file)).ok())
+      .as_ref()
+      .is_some_and(|manifest| manifest.contains(key))
   }
 
   pub(crate) fn matches(&self, rule: &dyn Rule) -> Result<Vec<PathBuf>> {
@@ -37,11 +51,9 @@ impl Context {
       .into_iter()
       .flat_map(|matcher| {
         self
-          .directories
-          .iter()
-          .chain(self.files.iter())
+          .paths()
           .filter(move |path| matcher.is_match(path))
-          .cloned()
+          .map(Path::to_path_buf)
       })
       .collect::<HashSet<_>>();
 
@@ -83,68 +95,148 @@ impl Context {
     Ok(pruned)
   }
 
-  pub(crate) fn modified_time(&self) -> Result<SystemTime> {
-    Ok(fs::metadata(&self.root)?.modified()?)
-  }
+  /// When the project was last worked on, as far as `rule` is concerned.
+  pub(crate) fn modified_time(&self, rule: &dyn Rule) -> Result<SystemTime> {
+    let targets = Index::targets(once(rule))?;
 
-  pub(crate) fn new(root: PathBuf, follow_symlinks: bool) -> Result<Self> {
-    let (mut directories, mut files) = (HashSet::new(), HashSet::new());
+    if self.index.age_source == AgeSource::Commit
+      && let Some(commit) = *self
+        .commit
+        .get_or_init(|| Repository::last_commit(&self.root).ok().flatten())
+    {
+      return Ok(commit);
+    }
 
-    for entry in WalkDir::new(&root).follow_links(follow_symlinks) {
-      let entry = entry?;
+    self.newest(&targets)
+  }
 
-      if entry.depth() == 0 {
-        continue;
-      }
+  /// The newest time of any file outside `targets` and `.git`, statting each
+  /// file only once however many rules ask.
+  fn newest(&self, targets: &GlobSet) -> Result<SystemTime> {
+    let time = match self.index.age_source {
+      AgeSource::Accessed => fs::Metadata::accessed,
+      AgeSource::Commit | AgeSource::Files => fs::Metadata::modified,
+    };
 
-      let relative = entry
-        .path()
-        .strip_prefix(&root)
-        .unwrap_or(entry.path())
-        .to_path_buf();
+    let newest = self
+      .times
+      .get_or_init(|| {
+        self
+          .index
+          .descendants(&self.relative)
+          .iter()
+          .filter(|entry| !entry.directory)
+          .filter(|entry| {
+            !entry
+              .path
+              .components()
+              .any(|component| component.as_os_str() == ".git")
+          })
+          .filter_map(|entry| {
+            let metadata =
+              fs::symlink_metadata(self.index.root.join(&entry.path)).ok()?;
+
+            Some((
+              entry
+                .path
+                .strip_prefix(&self.relative)
+                .unwrap_or(&entry.path)
+                .to_path_buf(),
+              time(&metadata).ok()?,
+            ))
+          })
+          .collect()
+      })
+      .iter()
+      .filter(|(path, _)| !Index::is_target(path, targets))
+      .map(|(_, time)| *time)
+      .max();
 
-      if entry.file_type().is_dir() {
-        directories.insert(relative);
-      } else {
-        files.insert(relative);
-      }
+    match newest {
+      Some(newest) => Ok(newest),
+      None => Ok(time(&fs::metadata(&self.root)?)?),
     }
+  }
 
-    Ok(Self {
-      directories,
-      files,
-      follow_symlinks,
-      root,
+  /// Paths of every entry below the context root, relative to it.
+  fn paths(&self) -> impl Iterator<Item = &Path> {
+    self.index.descendants(&self.relative).iter().map(|entry| {
+      entry
+        .path
+        .strip_prefix(&self.relative)
+        .unwrap_or(&entry.path)
     })
   }
 
-  pub(crate) fn report(&self, rule: &dyn Rule) -> Result<Report> {
-    let mut tasks = Vec::new();
+  /// Builds the rule's tasks, preferring commands whose tools are installed.
+  pub(crate) fn report(
+    &self,
+    rule: &dyn Rule,
+    usage: &Usage,
+  ) -> Result<Report> {
+    let commands = rule
+      .actions()
+      .iter()
+      .filter_map(|action| match action {
+        Action::Command(command) => Some(*command),
+        Action::Remove(_) => None,
+      })
+      .collect::<Vec<_>>();
 
-    for action in rule.actions() {
-      if let Action::Command(command) = action {
-        tasks.push(Task::Command(command));
-      }
-    }
+    let mut missing_tools = commands
+      .iter()
+      .filter(|command| !command.available(&self.root))
+      .filter_map(|command| command.tool.clone())
+      .collect::<Vec<_>>();
+
+    missing_tools.sort();
+    missing_tools.dedup();
+
+    let native = missing_tools.is_empty()
+      && commands.iter().any(|command| command.tool.is_some());
+
+    let mut tasks = commands
+      .into_iter()
+      .filter(|command| command.tool.is_none() || native)
+      .map(Task::Command)
+      .collect::<Vec<_>>();
 
-    for relative_path in self.matches(rule)? {
-      let full_path = self.root.join(&relative_path);
+    if !native {
+      for relative_path in self.matches(rule)? {
+        let full_path = self.root.join(&relative_path);
 
-      let bytes = full_path.size(self.follow_symlinks)?;
+        let bytes = full_path.size(self.follow_symlinks, usage)?;
 
-      tasks.push(Task::Remove {
-        path: relative_path,
-        size: bytes,
-      });
+        tasks.push(Task::Remove {
+          path: relative_path,
+          size: bytes,
+        });
+      }
     }
 
     Ok(Report {
-      modified: self.modified_time()?,
+      missing_tools,
+      modified: self.modified_time(rule)?,
       root: self.root.clone(),
+      rule_id: rule.id().to_string(),
       rule_name: rule.name().to_string(),
+      skip: None,
+      skipped: Vec::new(),
       tasks,
     })
   }
+
+  /// The rules that apply here, after the project's `.swab.toml`.
+  pub(crate) fn rules<'r>(
+    &'r self,
+    rules: &'r [Box<dyn Rule>],
+  ) -> impl Iterator<Item = &'r dyn Rule> {
+    rules
+      .iter()
+      .map(AsRef::as_ref)
+      .filter(|rule| self.local.enabled(*rule))
+      .chain(self.local.rule())
+  }
 }
 
 #[cfg(test)]
@@ -179,7 +271,16 @@ mod tests {
       "README.md": "hello",
     };
 
-    let context = Context::new(tree.path().to_path_buf(), false).unwrap();
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
 
     let rule = TestRule {
       actions: &[Action::Remove("nope/**")],
@@ -195,7 +296,16 @@ mod tests {
       "a.log": "a",
     };
 
-    let context = Context::new(tree.path().to_path_buf(), false).unwrap();
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
 
     let rule = TestRule {
       actions: &[Action::Remove("*.log")],
@@ -215,7 +325,11 @@ mod tests {
 
     let root = tree.path();
 
-    let context = Context::new(root.to_path_buf(), false).unwrap();
+    let index =
+      Index::new(root.to_path_buf(), AgeSource::Files, false, None, &[])
+        .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
 
     fs::remove_file(root.join("stale.log")).unwrap();
 
@@ -242,17 +356,29 @@ mod tests {
       "README.md": "hello",
     };
 
-    let context = Context::new(tree.path().to_path_buf(), false).unwrap();
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
 
     let rule = TestRule {
-      actions: &[
+      actions: Vec::leak(vec![
         Action::Remove("node_modules"),
         Action::Remove("node_modules/**"),
         Action::Remove("target"),
         Action::Remove("target/**"),
         Action::Remove("*.md"),
-        Action::Command("echo ignored"),
-      ],
+        Action::Command(Box::leak(Box::new(Invocation {
+          command: "echo ignored".into(),
+          ..Invocation::default()
+        }))),
+      ]),
     };
 
     assert_eq!(
@@ -264,4 +390,133 @@ mod tests {
       ],
     );
   }
+
+  #[test]
+  fn modified_time_ignores_artifacts() {
+    let tree = temptree! {
+      "Cargo.toml": "",
+      "src": {
+        "main.rs": "",
+      },
+      "target": {
+        "app": "",
+      },
+    };
+
+    let epoch = SystemTime::UNIX_EPOCH;
+
+    for (path, seconds) in [
+      ("", 1),
+      ("Cargo.toml", 2),
+      ("src", 1),
+      ("src/main.rs", 3),
+      ("target", 5),
+      ("target/app", 4),
+    ] {
+      filetime::set_file_mtime(
+        tree.path().join(path),
+        filetime::FileTime::from_system_time(
+          epoch + Duration::from_secs(seconds),
+        ),
+      )
+      .unwrap();
+    }
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &rules,
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
+
+    let cargo = rules.iter().find(|rule| rule.id() == "cargo").unwrap();
+
+    assert_eq!(
+      context.modified_time(cargo.as_ref()).unwrap(),
+      epoch + Duration::from_secs(3)
+    );
+  }
+
+  #[test]
+  fn modified_time_counts_other_rules_artifacts() {
+    let tree = temptree! {
+      "Cargo.toml": "",
+      "node_modules": {
+        "index.js": "",
+      },
+    };
+
+    let epoch = SystemTime::UNIX_EPOCH;
+
+    for (path, seconds) in [
+      ("", 1),
+      ("Cargo.toml", 2),
+      ("node_modules", 1),
+      ("node_modules/index.js", 3),
+    ] {
+      filetime::set_file_mtime(
+        tree.path().join(path),
+        filetime::FileTime::from_system_time(
+          epoch + Duration::from_secs(seconds),
+        ),
+      )
+      .unwrap();
+    }
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &rules,
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
+
+    let cargo = rules.iter().find(|rule| rule.id() == "cargo").unwrap();
+
+    assert_eq!(
+      context.modified_time(cargo.as_ref()).unwrap(),
+      epoch + Duration::from_secs(3)
+    );
+  }
+
+  #[test]
+  fn modified_time_falls_back_to_root() {
+    let tree = temptree! {
+      "empty": {},
+    };
+
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(7);
+
+    filetime::set_file_mtime(
+      tree.path().join("empty"),
+      filetime::FileTime::from_system_time(modified),
+    )
+    .unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("empty")).unwrap();
+
+    let rule = TestRule { actions: &[] };
+
+    assert_eq!(context.modified_time(&rule).unwrap(), modified);
+  }
 }
@@ -0,0 +1,256 @@
+use super::*;
+
+/// An age like `30d` or a date like `2026-01-01`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub(crate) enum Cutoff {
+  Age(Age),
+  Date(SystemTime),
+}
+
+impl Display for Cutoff {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Age(age) => write!(f, "{age}"),
+      Self::Date(date) => {
+        let seconds = date.timestamp();
+
+        let (year, month, day) =
+          Self::civil(i64::try_from(seconds / 86400).map_err(|_| fmt::Error)?);
+
+        write!(f, "{year:04}-{month:02}-{day:02}")?;
+
+        let time = seconds % 86400;
+
+        if time != 0 {
+          write!(
+            f,
+            "T{:02}:{:02}:{:02}Z",
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+          )?;
+        }
+
+        Ok(())
+      }
+    }
+  }
+}
+
+impl From<Cutoff> for String {
+  fn from(cutoff: Cutoff) -> Self {
+    cutoff.to_string()
+  }
+}
+
+impl FromStr for Cutoff {
+  type Err = Error;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let text = text.trim();
+
+    let date = text.len() >= 5
+      && text.as_bytes()[..4].iter().all(u8::is_ascii_digit)
+      && text.as_bytes()[4] == b'-';
+
+    if date {
+      Self::date(text)
+        .map(Self::Date)
+        .ok_or_else(|| anyhow!("invalid date: `{text}`"))
+    } else {
+      text.parse().map(Self::Age)
+    }
+  }
+}
+
+impl TryFrom<String> for Cutoff {
+  type Error = Error;
+
+  fn try_from(text: String) -> Result<Self> {
+    text.parse()
+  }
+}
+
+impl Cutoff {
+  /// The cutoff as a point in time, with ages counted back from `now`.
+  pub(crate) fn at(self, now: SystemTime) -> SystemTime {
+    match self {
+      Self::Age(age) => now.checked_sub(age.0).unwrap_or(UNIX_EPOCH),
+      Self::Date(date) => date,
+    }
+  }
+
+  /// Converts days since the Unix epoch to a year, month and day.
+  fn civil(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+      - day_of_era / 146_096)
+      / 365;
+    let day_of_year =
+      day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+  }
+
+  /// Parses `YYYY-MM-DD[THH:MM[:SS]][Z|±HH:MM]`, defaulting to UTC.
+  fn date(text: &str) -> Option<SystemTime> {
+    let (date, time) = text.split_once(['T', ' ']).unwrap_or((text, ""));
+
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day)), None) =
+      (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+      return None;
+    };
+
+    let days = Self::days(year, month, day);
+
+    if Self::civil(days) != (year, month, day) {
+      return None;
+    }
+
+    let (time, offset) = if time.is_empty() {
+      ("00:00", 0)
+    } else if let Some(time) = time.strip_suffix('Z') {
+      (time, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+      let (time, offset) = time.split_at(index);
+      let sign = if offset.starts_with('-') { -1 } else { 1 };
+      let (hours, minutes) = offset[1..].split_once(':')?;
+      let hours = hours.parse::<i64>().ok().filter(|hours| *hours < 24)?;
+      let minutes = minutes
+        .parse::<i64>()
+        .ok()
+        .filter(|minutes| *minutes < 60)?;
+      (time, sign * (hours * 3600 + minutes * 60))
+    } else {
+      (time, 0)
+    };
+
+    let mut parts = time.split(':').map(|part| part.parse::<i64>().ok());
+
+    let (Some(Some(hours)), Some(Some(minutes)), seconds, None) =
+      (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+      return None;
+    };
+
+    let seconds = seconds.unwrap_or(Some(0))?;
+
+    if hours >= 24 || minutes >= 60 || seconds >= 60 {
+      return None;
+    }
+
+    let timestamp =
+      days * 86400 + hours * 3600 + minutes * 60 + seconds - offset;
+
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(timestamp).ok()?))
+  }
+
+  /// Converts a year, month and day to days since the Unix epoch.
+  fn days(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era =
+      year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+  }
+
+  /// Whether `time` is after the cutoff.
+  pub(crate) fn newer_than(self, time: SystemTime) -> bool {
+    time > self.at(SystemTime::now())
+  }
+
+  /// Whether `time` is before the cutoff.
+  pub(crate) fn older_than(self, time: SystemTime) -> bool {
+    time < self.at(SystemTime::now())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(seconds: u64) -> Cutoff {
+    Cutoff::Date(UNIX_EPOCH + Duration::from_secs(seconds))
+  }
+
+  #[test]
+  fn parsing() {
+    #[track_caller]
+    fn case(text: &str, expected: Cutoff) {
+      assert_eq!(text.parse::<Cutoff>().unwrap(), expected);
+    }
+
+    case("30d", Cutoff::Age(Age(Duration::from_hours(24 * 30))));
+    case("P2W", Cutoff::Age(Age(Duration::from_hours(24 * 14))));
+    case("1970-01-01", date(0));
+    case("1970-01-02", date(86400));
+    case("2000-02-29", date(951_782_400));
+    case("2026-01-01", date(1_767_225_600));
+    case("2026-01-01T09:30", date(1_767_259_800));
+    case("2026-01-01 09:30:15", date(1_767_259_815));
+    case("2026-01-01T09:30:15Z", date(1_767_259_815));
+    case("2026-01-01T09:30:15+02:00", date(1_767_252_615));
+    case("2026-01-01T09:30:15-01:30", date(1_767_265_215));
+  }
+
+  #[test]
+  fn parse_invalid_date() {
+    for text in [
+      "2026-13-01",
+      "2026-02-29",
+      "2026-01-00",
+      "2026-01",
+      "2026-01-01T25:00",
+      "2026-01-01T09",
+      "2026-01-01T09:30+2",
+      "1969-12-31",
+    ] {
+      assert_eq!(
+        text.parse::<Cutoff>().unwrap_err().to_string(),
+        format!("invalid date: `{text}`"),
+      );
+    }
+  }
+
+  #[test]
+  fn display_round_trips() {
+    #[track_caller]
+    fn case(text: &str, expected: &str) {
+      let cutoff = text.parse::<Cutoff>().unwrap();
+      assert_eq!(cutoff.to_string(), expected);
+      assert_eq!(expected.parse::<Cutoff>().unwrap(), cutoff);
+    }
+
+    case("7d", "1w");
+    case("2026-01-01", "2026-01-01");
+    case("2026-01-01T09:30+02:00", "2026-01-01T07:30:00Z");
+  }
+
+  #[test]
+  fn older_and_newer_than() {
+    let now = SystemTime::now();
+
+    let age = Cutoff::Age(Age(Duration::from_hours(1)));
+
+    assert!(age.older_than(now - Duration::from_hours(2)));
+    assert!(!age.newer_than(now - Duration::from_hours(2)));
+    assert!(!age.older_than(now));
+    assert!(age.newer_than(now));
+
+    let date = date(86400);
+
+    assert!(date.older_than(UNIX_EPOCH));
+    assert!(date.newer_than(now));
+  }
+}
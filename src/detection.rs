@@ -4,6 +4,10 @@ use super::*;
 pub(crate) enum Detection {
   All(Box<Detection>, Box<Detection>),
   Any(Box<Detection>, Box<Detection>),
+  /// A file, relative to the project root, whose contents match a regex.
+  Contains(&'static str, Regex),
+  /// A manifest, relative to the project root, with a dotted key present.
+  Key(&'static str, &'static str),
   Not(Box<Detection>),
   Pattern(&'static str),
 }
@@ -13,8 +17,12 @@ impl Display for Detection {
     match self {
       Self::All(left, right) => write!(f, "({left} AND {right})"),
       Self::Any(left, right) => write!(f, "({left} OR {right})"),
+      Self::Contains(file, regex) => {
+        write!(f, "contains({file:?}, {:?})", regex.as_str())
+      }
+      Self::Key(file, key) => write!(f, "key({file:?}, {key:?})"),
       Self::Not(inner) => write!(f, "NOT {inner}"),
-      Self::Pattern(pattern) => write!(f, "{pattern}"),
+      Self::Pattern(pattern) => write!(f, "{}", Expression::quote(pattern)),
     }
   }
 }
@@ -24,8 +32,10 @@ impl TryFrom<ConfigDetection> for Detection {
 
   fn try_from(value: ConfigDetection) -> Result<Self> {
     match value {
-      ConfigDetection::Pattern(pattern)
-      | ConfigDetection::PatternMap { pattern } => {
+      ConfigDetection::Pattern(expression) => {
+        Expression::parse(&expression)?.try_into()
+      }
+      ConfigDetection::PatternMap { pattern } => {
         ensure!(
           !pattern.trim().is_empty(),
           "detection pattern cannot be empty"
@@ -40,6 +50,25 @@ impl TryFrom<ConfigDetection> for Detection {
       ConfigDetection::Any { any } => {
         ConfigDetection::fold(any, Detection::Any, "any")
       }
+      ConfigDetection::Contains { contains, file } => {
+        ensure!(!file.trim().is_empty(), "detection file cannot be empty");
+
+        let regex = Regex::new(&contains).map_err(|error| {
+          anyhow!("invalid detection regex `{contains}`: {error}")
+        })?;
+
+        Ok(Detection::Contains(Box::leak(file.into_boxed_str()), regex))
+      }
+      ConfigDetection::Key { file, key } => {
+        Manifest::syntax(Path::new(&file))?;
+
+        ensure!(!key.trim().is_empty(), "detection key cannot be empty");
+
+        Ok(Detection::Key(
+          Box::leak(file.into_boxed_str()),
+          Box::leak(key.into_boxed_str()),
+        ))
+      }
       ConfigDetection::All { all } => {
         ConfigDetection::fold(all, Detection::All, "all")
       }
@@ -55,8 +84,25 @@ impl Detection {
     match self {
       Self::All(left, right) => left.matches(context) && right.matches(context),
       Self::Any(left, right) => left.matches(context) || right.matches(context),
+      Self::Contains(file, regex) => {
+        fs::read_to_string(context.root.join(file))
+          .is_ok_and(|contents| regex.is_match(&contents))
+      }
+      Self::Key(file, key) => context.has_key(file, key),
       Self::Not(inner) => !inner.matches(context),
       Self::Pattern(pattern) => context.contains(pattern),
     }
   }
+
+  /// Every glob pattern in the detection.
+  pub(crate) fn patterns(&self) -> Vec<&'static str> {
+    match self {
+      Self::All(left, right) | Self::Any(left, right) => {
+        [left.patterns(), right.patterns()].concat()
+      }
+      Self::Contains(..) | Self::Key(..) => Vec::new(),
+      Self::Not(inner) => inner.patterns(),
+      Self::Pattern(pattern) => vec![pattern],
+    }
+  }
 }
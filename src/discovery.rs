@@ -0,0 +1,294 @@
+use super::*;
+
+#[derive(Debug)]
+pub(crate) struct Discovery {
+  pub(crate) max_depth: Option<usize>,
+  pub(crate) nested: bool,
+}
+
+impl Discovery {
+  /// Whether the rule has something to do in the project it detected.
+  fn has_work(context: &Context, rule: &dyn Rule) -> Result<bool> {
+    if rule
+      .actions()
+      .iter()
+      .any(|action| matches!(action, Action::Command(_)))
+    {
+      return Ok(true);
+    }
+
+    Ok(!context.matches(rule)?.is_empty())
+  }
+
+  /// Returns a context for every project in `index` with something to clean.
+  ///
+  /// A detected project claims the directories below it even when it has
+  /// nothing to clean, except those holding what detected it, which a
+  /// recursive pattern like `**/*.csproj` found on their behalf.
+  pub(crate) fn run<'a>(
+    &self,
+    index: &'a Index,
+    rules: &[Box<dyn Rule>],
+  ) -> Result<Vec<Context<'a>>> {
+    let mut contexts = Vec::new();
+
+    let mut claimed: Option<(&Path, HashSet<usize>, bool)> = None;
+
+    for directory in index.directories() {
+      if self
+        .max_depth
+        .is_some_and(|max_depth| directory.components().count() > max_depth)
+      {
+        continue;
+      }
+
+      if !self.nested
+        && let Some((root, patterns, work)) = &claimed
+        && directory.starts_with(root)
+        && (*work || index.detected(root, directory).is_disjoint(patterns))
+      {
+        continue;
+      }
+
+      let context = index.context(directory)?;
+
+      let detected = context
+        .rules(rules)
+        .filter(|rule| rule.detection().matches(&context))
+        .collect::<Vec<_>>();
+
+      if !context.local.skip && detected.is_empty() {
+        continue;
+      }
+
+      let work =
+        detected.iter().try_fold(context.local.skip, |work, rule| {
+          Ok::<_, Error>(work || Self::has_work(&context, *rule)?)
+        })?;
+
+      claimed = Some((
+        directory,
+        detected
+          .iter()
+          .flat_map(|rule| rule.detection().patterns())
+          .filter_map(|pattern| index.pattern(pattern))
+          .collect(),
+        work,
+      ));
+
+      if work {
+        contexts.push(context);
+      }
+    }
+
+    Ok(contexts)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  fn roots(discovery: &Discovery, root: &Path) -> Vec<PathBuf> {
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index =
+      Index::new(root.to_path_buf(), AgeSource::Files, false, None, &rules)
+        .unwrap();
+
+    discovery
+      .run(&index, &rules)
+      .unwrap()
+      .into_iter()
+      .map(|context| {
+        context
+          .root
+          .strip_prefix(root)
+          .unwrap_or(&context.root)
+          .to_path_buf()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn finds_deeply_nested_projects() {
+    let tree = temptree! {
+      "org": {
+        "team": {
+          "repo": {
+            "Cargo.toml": "",
+            "target": {
+              "app": "",
+            },
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: false,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("org/team/repo")],
+    );
+  }
+
+  #[test]
+  fn respects_max_depth() {
+    let tree = temptree! {
+      "shallow": {
+        "package.json": "",
+        "node_modules": {
+          "index.js": "",
+        },
+      },
+      "org": {
+        "team": {
+          "repo": {
+            "Cargo.toml": "",
+            "target": {
+              "app": "",
+            },
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: Some(2),
+      nested: false,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("shallow")],
+    );
+  }
+
+  #[test]
+  fn stops_at_claimed_projects() {
+    let tree = temptree! {
+      "workspace": {
+        "Cargo.toml": "",
+        "target": {
+          "app": "",
+        },
+        "web": {
+          "package.json": "",
+          "node_modules": {
+            "index.js": "",
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: false,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("workspace")],
+    );
+  }
+
+  #[test]
+  fn projects_without_work_still_claim_their_directories() {
+    let tree = temptree! {
+      "workspace": {
+        "Cargo.toml": "",
+        "web": {
+          "package.json": "",
+          "node_modules": {
+            "index.js": "",
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: false,
+    };
+
+    assert!(roots(&discovery, tree.path()).is_empty());
+  }
+
+  #[test]
+  fn ignores_matches_without_work() {
+    let tree = temptree! {
+      "project": {
+        "App.csproj": "",
+        "bin": {
+          "App.dll": "",
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: false,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("project")],
+    );
+  }
+
+  #[test]
+  fn finds_projects_inside_unclaimed_targets() {
+    let tree = temptree! {
+      "build": {
+        "app": {
+          "Cargo.toml": "",
+          "target": {
+            "app": "",
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: false,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("build/app")],
+    );
+  }
+
+  #[test]
+  fn nested_continues_into_claimed_projects() {
+    let tree = temptree! {
+      "workspace": {
+        "Cargo.toml": "",
+        "target": {
+          "app": "",
+        },
+        "web": {
+          "package.json": "",
+          "node_modules": {
+            "index.js": "",
+          },
+        },
+      },
+    };
+
+    let discovery = Discovery {
+      max_depth: None,
+      nested: true,
+    };
+
+    assert_eq!(
+      roots(&discovery, tree.path()),
+      vec![PathBuf::from("workspace"), PathBuf::from("workspace/web")],
+    );
+  }
+}
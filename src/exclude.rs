@@ -0,0 +1,139 @@
+use super::*;
+
+/// Globs naming paths swab must never touch.
+#[derive(Debug, Default)]
+pub(crate) struct Exclude {
+  absolute: GlobSet,
+  /// The absolute patterns without wildcards.
+  prefixes: Vec<PathBuf>,
+  relative: GlobSet,
+}
+
+impl Exclude {
+  /// Whether an absolute pattern without wildcards names something below
+  /// `path`. Everything else is caught by `matches` while measuring `path`.
+  pub(crate) fn contains(&self, path: &Path) -> bool {
+    if self.prefixes.is_empty() {
+      return false;
+    }
+
+    let Ok(path) = std::path::absolute(path) else {
+      return false;
+    };
+
+    self
+      .prefixes
+      .iter()
+      .any(|prefix| *prefix != path && prefix.starts_with(&path))
+  }
+
+  pub(crate) fn excludes(&self, path: &Path) -> bool {
+    if self.absolute.is_empty() && self.relative.is_empty() {
+      return false;
+    }
+
+    let Ok(path) = std::path::absolute(path) else {
+      return false;
+    };
+
+    path.ancestors().any(|ancestor| self.matches(ancestor))
+  }
+
+  pub(crate) fn matches(&self, path: &Path) -> bool {
+    if !self.absolute.is_empty()
+      && std::path::absolute(path)
+        .is_ok_and(|path| self.absolute.is_match(path))
+    {
+      return true;
+    }
+
+    !self.relative.is_empty()
+      && path
+        .ancestors()
+        .skip(1)
+        .filter_map(|parent| path.strip_prefix(parent).ok())
+        .any(|suffix| self.relative.is_match(suffix))
+  }
+
+  pub(crate) fn new<T: AsRef<str>>(patterns: &[T]) -> Result<Self> {
+    let (mut absolute, mut prefixes, mut relative) =
+      (GlobSetBuilder::new(), Vec::new(), GlobSetBuilder::new());
+
+    for pattern in patterns {
+      let pattern = pattern.as_ref();
+
+      let expanded = match pattern.strip_prefix("~/") {
+        Some(rest) => etcetera::home_dir()?
+          .join(rest)
+          .to_string_lossy()
+          .into_owned(),
+        None => pattern.to_owned(),
+      };
+
+      let glob = Glob::new(&expanded).map_err(|error| {
+        anyhow!("invalid exclude pattern `{pattern}`: {error}")
+      })?;
+
+      if Path::new(&expanded).is_absolute() {
+        if !expanded.contains(['*', '?', '[', '{']) {
+          prefixes.push(PathBuf::from(&expanded));
+        }
+
+        absolute.add(glob);
+      } else {
+        relative.add(glob);
+      }
+    }
+
+    Ok(Self {
+      absolute: absolute.build()?,
+      prefixes,
+      relative: relative.build()?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn relative_patterns_match_anywhere() {
+    let exclude = Exclude::new(&["vendor/foo/target"]).unwrap();
+
+    assert!(exclude.excludes(Path::new("/code/app/vendor/foo/target")));
+    assert!(exclude.excludes(Path::new("/code/app/vendor/foo/target/debug")));
+    assert!(!exclude.excludes(Path::new("/code/app/target")));
+  }
+
+  #[test]
+  fn absolute_patterns_match_subtrees() {
+    let exclude = Exclude::new(&["/code/critical"]).unwrap();
+
+    assert!(exclude.excludes(Path::new("/code/critical")));
+    assert!(exclude.excludes(Path::new("/code/critical/app/target")));
+    assert!(!exclude.excludes(Path::new("/code/other/critical")));
+  }
+
+  #[test]
+  fn home_is_expanded() {
+    let exclude = Exclude::new(&["~/code"]).unwrap();
+
+    assert!(exclude.excludes(&etcetera::home_dir().unwrap().join("code/app")));
+  }
+
+  #[test]
+  fn contains_checks_absolute_paths_below_the_path() {
+    let exclude = Exclude::new(&["/code/app/vendor", "vendor/foo"]).unwrap();
+
+    assert!(exclude.contains(Path::new("/code/app")));
+    assert!(!exclude.contains(Path::new("/code/app/vendor")));
+    assert!(!exclude.contains(Path::new("/code/other")));
+    assert!(!exclude.contains(Path::new("/code/vendor")));
+  }
+
+  #[test]
+  fn empty_excludes_nothing() {
+    assert!(!Exclude::default().excludes(Path::new("/")));
+  }
+}
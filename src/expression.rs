@@ -0,0 +1,465 @@
+use super::*;
+
+/// Parses detection expressions like `(*.csproj | *.fsproj) & !project.godot`.
+#[derive(Debug)]
+pub(crate) struct Expression<'a> {
+  position: usize,
+  source: &'a str,
+  tokens: Vec<(usize, Token)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+  And,
+  Close,
+  Comma,
+  Function(String),
+  Not,
+  Open,
+  Or,
+  Pattern(String),
+}
+
+impl Display for Token {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::And => write!(f, "`&`"),
+      Self::Close => write!(f, "`)`"),
+      Self::Comma => write!(f, "`,`"),
+      Self::Function(name) => write!(f, "`{name}(`"),
+      Self::Not => write!(f, "`!`"),
+      Self::Open => write!(f, "`(`"),
+      Self::Or => write!(f, "`|`"),
+      Self::Pattern(pattern) => write!(f, "pattern `{pattern}`"),
+    }
+  }
+}
+
+impl<'a> Expression<'a> {
+  const FUNCTIONS: [&'static str; 2] = ["contains", "key"];
+
+  fn and(&mut self) -> Result<ConfigDetection> {
+    let mut all = vec![self.unary()?];
+
+    while self.eat(&Token::And) {
+      all.push(self.unary()?);
+    }
+
+    Ok(Self::combine(all, |all| ConfigDetection::All { all }))
+  }
+
+  fn arguments(&mut self, name: &str) -> Result<Vec<String>> {
+    let mut arguments = Vec::new();
+
+    loop {
+      match self.next() {
+        Some((_, Token::Pattern(argument))) => arguments.push(argument),
+        Some((column, token)) => {
+          return Err(self.error(column, &format!("unexpected {token}")));
+        }
+        None => return Err(self.error_at_end("expected an argument")),
+      }
+
+      match self.next() {
+        Some((_, Token::Comma)) => {}
+        Some((_, Token::Close)) => break,
+        Some((column, token)) => {
+          return Err(self.error(
+            column,
+            &format!("expected `,` or `)` in `{name}(`, found {token}"),
+          ));
+        }
+        None => return Err(self.error_at_end("expected `)`")),
+      }
+    }
+
+    Ok(arguments)
+  }
+
+  /// Joins operands of one operator, leaving a lone operand as it is.
+  fn combine(
+    mut operands: Vec<ConfigDetection>,
+    join: fn(Vec<ConfigDetection>) -> ConfigDetection,
+  ) -> ConfigDetection {
+    if operands.len() == 1 {
+      operands.remove(0)
+    } else {
+      join(operands)
+    }
+  }
+
+  fn eat(&mut self, token: &Token) -> bool {
+    if self.peek() == Some(token) {
+      self.position += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn error(&self, column: usize, message: &str) -> Error {
+    anyhow!(
+      "invalid detection expression `{}`: {message} at column {}",
+      self.source,
+      column + 1
+    )
+  }
+
+  fn error_at_end(&self, message: &str) -> Error {
+    self.error(self.source.chars().count(), message)
+  }
+
+  fn function(&mut self, column: usize, name: &str) -> Result<ConfigDetection> {
+    let arguments = self.arguments(name)?;
+
+    let [file, argument] =
+      <[String; 2]>::try_from(arguments).map_err(|_| {
+        self.error(column, &format!("`{name}` takes exactly two arguments"))
+      })?;
+
+    Ok(match name {
+      "contains" => ConfigDetection::Contains {
+        contains: argument,
+        file,
+      },
+      _ => ConfigDetection::Key {
+        file,
+        key: argument,
+      },
+    })
+  }
+
+  fn lex(source: &str) -> Result<Vec<(usize, Token)>, (usize, String)> {
+    let chars = source.chars().collect::<Vec<char>>();
+
+    let mut tokens = Vec::new();
+
+    let mut i = 0;
+
+    while i < chars.len() {
+      let start = i;
+
+      let token = match chars[i] {
+        c if c.is_whitespace() => {
+          i += 1;
+          continue;
+        }
+        '&' => Token::And,
+        ')' => Token::Close,
+        ',' => Token::Comma,
+        '!' => Token::Not,
+        '(' => Token::Open,
+        '|' => Token::Or,
+        '"' => {
+          let (string, end) = Self::string(&chars, i)?;
+          i = end;
+          tokens.push((start, Token::Pattern(string)));
+          continue;
+        }
+        _ => {
+          let mut depth = 0usize;
+
+          while i < chars.len() {
+            match chars[i] {
+              '[' | '{' => depth += 1,
+              ']' | '}' => depth = depth.saturating_sub(1),
+              c if depth == 0
+                && (c.is_whitespace() || "()&|,\"".contains(c)) =>
+              {
+                break;
+              }
+              _ => {}
+            }
+
+            i += 1;
+          }
+
+          let word = chars[start..i].iter().collect::<String>();
+
+          let token = match word.as_str() {
+            "AND" => Token::And,
+            "NOT" => Token::Not,
+            "OR" => Token::Or,
+            name
+              if Self::FUNCTIONS.contains(&name)
+                && chars.get(i) == Some(&'(') =>
+            {
+              i += 1;
+              Token::Function(word)
+            }
+            _ => Token::Pattern(word),
+          };
+
+          tokens.push((start, token));
+
+          continue;
+        }
+      };
+
+      tokens.push((start, token));
+
+      i += 1;
+    }
+
+    Ok(tokens)
+  }
+
+  fn next(&mut self) -> Option<(usize, Token)> {
+    let token = self.tokens.get(self.position).cloned();
+    self.position += 1;
+    token
+  }
+
+  fn or(&mut self) -> Result<ConfigDetection> {
+    let mut any = vec![self.and()?];
+
+    while self.eat(&Token::Or) {
+      any.push(self.and()?);
+    }
+
+    Ok(Self::combine(any, |any| ConfigDetection::Any { any }))
+  }
+
+  pub(crate) fn parse(source: &'a str) -> Result<ConfigDetection> {
+    let mut expression = Self {
+      position: 0,
+      source,
+      tokens: Vec::new(),
+    };
+
+    expression.tokens = Self::lex(source)
+      .map_err(|(column, message)| expression.error(column, &message))?;
+
+    // A string without operators, quotes or functions is a literal glob, as
+    // detections were before they could be expressions.
+    if !source.contains('"')
+      && !expression.tokens.is_empty()
+      && expression
+        .tokens
+        .iter()
+        .all(|(_, token)| matches!(token, Token::Pattern(_)))
+    {
+      return Ok(ConfigDetection::PatternMap {
+        pattern: source.to_owned(),
+      });
+    }
+
+    let detection = expression.or()?;
+
+    if let Some((column, token)) = expression.next() {
+      return Err(expression.error(column, &format!("unexpected {token}")));
+    }
+
+    Ok(detection)
+  }
+
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position).map(|(_, token)| token)
+  }
+
+  fn primary(&mut self) -> Result<ConfigDetection> {
+    match self.next() {
+      Some((_, Token::Pattern(pattern))) => {
+        Ok(ConfigDetection::PatternMap { pattern })
+      }
+      Some((column, Token::Function(name))) => self.function(column, &name),
+      Some((column, Token::Open)) => {
+        let detection = self.or()?;
+
+        match self.next() {
+          Some((_, Token::Close)) => Ok(detection),
+          Some((column, token)) => {
+            Err(self.error(column, &format!("expected `)`, found {token}")))
+          }
+          None => Err(self.error(column, "unclosed `(`")),
+        }
+      }
+      Some((column, token)) => {
+        Err(self.error(column, &format!("expected a pattern, found {token}")))
+      }
+      None => Err(self.error_at_end("expected a pattern")),
+    }
+  }
+
+  /// Quotes `pattern` when it would otherwise be read as an operator.
+  pub(crate) fn quote(pattern: &str) -> Cow<'_, str> {
+    let bare = Self::lex(pattern).is_ok_and(|tokens| {
+      matches!(tokens.as_slice(), [(0, Token::Pattern(word))] if word == pattern)
+    }) && !pattern.starts_with('"');
+
+    if bare {
+      Cow::Borrowed(pattern)
+    } else {
+      Cow::Owned(format!("{pattern:?}"))
+    }
+  }
+
+  /// Reads a double quoted string, returning it and the index past its end.
+  fn string(
+    chars: &[char],
+    start: usize,
+  ) -> Result<(String, usize), (usize, String)> {
+    let mut string = String::new();
+
+    let mut i = start + 1;
+
+    loop {
+      match chars.get(i) {
+        None => return Err((start, "unterminated string".into())),
+        Some('"') => return Ok((string, i + 1)),
+        Some('\\') => {
+          let escaped = match chars.get(i + 1) {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"' | '\'')) => *c,
+            Some('u') if chars.get(i + 2) == Some(&'{') => {
+              let end = chars[i + 3..]
+                .iter()
+                .position(|c| *c == '}')
+                .map(|offset| i + 3 + offset)
+                .ok_or((i, "unterminated unicode escape".to_owned()))?;
+
+              let code = chars[i + 3..end].iter().collect::<String>();
+
+              let c = u32::from_str_radix(&code, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or((i, format!("invalid unicode escape `{code}`")))?;
+
+              string.push(c);
+
+              i = end + 1;
+
+              continue;
+            }
+            _ => return Err((i, "invalid escape".into())),
+          };
+
+          string.push(escaped);
+
+          i += 2;
+        }
+        Some(c) => {
+          string.push(*c);
+          i += 1;
+        }
+      }
+    }
+  }
+
+  fn unary(&mut self) -> Result<ConfigDetection> {
+    if self.eat(&Token::Not) {
+      return Ok(ConfigDetection::Not {
+        not: Box::new(self.unary()?),
+      });
+    }
+
+    self.primary()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[track_caller]
+  fn case(source: &str, expected: &str) {
+    assert_eq!(Expression::parse(source).unwrap().to_string(), expected);
+  }
+
+  #[track_caller]
+  fn error(source: &str, expected: &str) {
+    assert_eq!(
+      Expression::parse(source).unwrap_err().to_string(),
+      format!("invalid detection expression `{source}`: {expected}"),
+    );
+  }
+
+  #[test]
+  fn single_pattern() {
+    case("Cargo.toml", "Cargo.toml");
+    case("**/*.csproj", "**/*.csproj");
+    case("*.{c,h}", "*.{c,h}");
+    case("[!.]*", "[!.]*");
+    case("My Project.sln", r#""My Project.sln""#);
+  }
+
+  #[test]
+  fn precedence() {
+    case("a | b & c", "(a OR (b AND c))");
+    case("a & b | c", "((a AND b) OR c)");
+    case("!a & b", "(NOT a AND b)");
+    case("a & b & c", "(a AND b AND c)");
+  }
+
+  #[test]
+  fn parentheses() {
+    case(
+      "(*.csproj | *.fsproj) & !project.godot",
+      "((*.csproj OR *.fsproj) AND NOT project.godot)",
+    );
+  }
+
+  #[test]
+  fn keywords() {
+    case("a AND NOT (b OR c)", "(a AND NOT (b OR c))");
+  }
+
+  #[test]
+  fn quoted_patterns() {
+    case(
+      r#""my file (1).txt" | and"#,
+      r#"("my file (1).txt" OR and)"#,
+    );
+    case(r#""AND""#, r#""AND""#);
+    case(r#""tab\there""#, r#""tab\there""#);
+  }
+
+  #[test]
+  fn functions() {
+    case(
+      r#"key("package.json", "dependencies.next") & contains(Makefile, "a|b")"#,
+      r#"(key("package.json", "dependencies.next") AND contains("Makefile", "a|b"))"#,
+    );
+  }
+
+  #[test]
+  fn display_round_trips() {
+    for source in [
+      "(a | b) & !(c & d)",
+      r#""a b" | "\"quoted\"" | "NOT""#,
+      r#"contains(Makefile, "\\s+") | !key("Cargo.toml", "workspace")"#,
+    ] {
+      let once = Expression::parse(source).unwrap().to_string();
+
+      let twice = Expression::parse(&once).unwrap().to_string();
+
+      assert_eq!(once, twice);
+    }
+  }
+
+  #[test]
+  fn built_in_rules_round_trip() {
+    for rule in Config::default_rules() {
+      let displayed = rule.detection().to_string();
+
+      let parsed =
+        Detection::try_from(Expression::parse(&displayed).unwrap()).unwrap();
+
+      assert_eq!(parsed.to_string(), displayed, "rule `{}`", rule.id());
+    }
+  }
+
+  #[test]
+  fn errors() {
+    error("", "expected a pattern at column 1");
+    error("a &", "expected a pattern at column 4");
+    error("(a | b", "unclosed `(` at column 1");
+    error("a b | c", "unexpected pattern `b` at column 3");
+    error("a | )", "expected a pattern, found `)` at column 5");
+    error(r#""abc"#, "unterminated string at column 1");
+    error("key(a)", "`key` takes exactly two arguments at column 1");
+  }
+}
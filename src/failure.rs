@@ -0,0 +1,108 @@
+use super::*;
+
+/// A project or task that failed under `--keep-going`.
+#[derive(Debug)]
+pub(crate) struct Failure {
+  pub(crate) error: Error,
+  pub(crate) root: PathBuf,
+  pub(crate) rule_id: Option<String>,
+}
+
+impl Display for Failure {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let style = Style::stdout();
+
+    write!(f, "{}", style.apply(BOLD, self.root.display()))?;
+
+    if let Some(rule_id) = &self.rule_id {
+      write!(f, " {}", style.apply(DIM, format_args!("({rule_id})")))?;
+    }
+
+    writeln!(f)?;
+
+    for (index, error) in self.error.chain().enumerate() {
+      let prefix = if index == 0 { "error" } else { "because" };
+
+      let message = error.to_string();
+
+      let mut lines = message.lines();
+
+      writeln!(
+        f,
+        "  {}: {}",
+        style.apply(RED, prefix),
+        lines.next().unwrap_or_default()
+      )?;
+
+      for line in lines {
+        writeln!(f, "    {line}")?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl Serialize for Failure {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("Failure", 3)?;
+    state.serialize_field("root", &self.root)?;
+    state.serialize_field("rule_id", &self.rule_id)?;
+    state.serialize_field(
+      "errors",
+      &self
+        .error
+        .chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>(),
+    )?;
+    state.end()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display_includes_the_error_chain() {
+    let failure = Failure {
+      error: anyhow!("permission denied").context("failed to remove `target`"),
+      root: PathBuf::from("/projects/app"),
+      rule_id: Some("cargo".into()),
+    };
+
+    assert_eq!(
+      failure.to_string(),
+      "/projects/app (cargo)\n  error: failed to remove `target`\n  because: permission denied\n",
+    );
+  }
+
+  #[test]
+  fn display_indents_multiline_errors() {
+    let failure = Failure {
+      error: anyhow!("command `make clean` failed:\nno rule\nstop"),
+      root: PathBuf::from("/projects/app"),
+      rule_id: Some("make".into()),
+    };
+
+    assert_eq!(
+      failure.to_string(),
+      "/projects/app (make)\n  error: command `make clean` failed:\n    no rule\n    stop\n",
+    );
+  }
+
+  #[test]
+  fn display_without_a_rule() {
+    let failure = Failure {
+      error: anyhow!("permission denied"),
+      root: PathBuf::from("/projects/app"),
+      rule_id: None,
+    };
+
+    assert_eq!(
+      failure.to_string(),
+      "/projects/app\n  error: permission denied\n",
+    );
+  }
+}
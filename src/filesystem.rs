@@ -0,0 +1,94 @@
+use super::*;
+
+/// The filesystem a path lives on, as far as `--free` cares about it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Filesystem {
+  pub(crate) available: u64,
+  pub(crate) device: u64,
+}
+
+impl Filesystem {
+  /// The distinct filesystems `paths` live on, each with a path on it.
+  pub(crate) fn distinct<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+  ) -> Result<Vec<(PathBuf, Self)>> {
+    let mut filesystems = Vec::<(PathBuf, Self)>::new();
+
+    for path in paths {
+      let filesystem = Self::of(path)?;
+
+      if !filesystems
+        .iter()
+        .any(|(_, existing)| existing.device == filesystem.device)
+      {
+        filesystems.push((path.to_path_buf(), filesystem));
+      }
+    }
+
+    Ok(filesystems)
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn of(path: &Path) -> Result<Self> {
+    use std::{
+      ffi::CString, os::unix::ffi::OsStrExt, os::unix::fs::MetadataExt,
+    };
+
+    let device = fs::metadata(path)?.dev();
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+
+    let mut stat = mem::MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: `c_path` is a valid, nul-terminated string and `stat` points to
+    // enough memory for a `statvfs`, which is only read if the call succeeds.
+    let result = unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+
+    if result != 0 {
+      return Err(anyhow!(
+        "failed to read free space of `{}`: {}",
+        path.display(),
+        io::Error::last_os_error()
+      ));
+    }
+
+    // SAFETY: `statvfs` returned success, so it initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+
+    #[allow(clippy::useless_conversion)]
+    let available = u64::from(stat.f_bavail) * u64::from(stat.f_frsize);
+
+    Ok(Self { available, device })
+  }
+
+  #[cfg(not(unix))]
+  pub(crate) fn of(_path: &Path) -> Result<Self> {
+    bail!("`--free` is only supported on Unix")
+  }
+
+  /// How much space has been freed on `filesystems` since they were measured.
+  pub(crate) fn reclaimed(filesystems: &[(PathBuf, Self)]) -> Result<u64> {
+    filesystems.iter().try_fold(0, |total, (path, before)| {
+      Ok(total + Self::of(path)?.available.saturating_sub(before.available))
+    })
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  #[test]
+  fn paths_on_the_same_filesystem_share_a_device() {
+    let tree = temptree! {
+      "a": {},
+      "b": {},
+    };
+
+    let a = Filesystem::of(&tree.path().join("a")).unwrap();
+    let b = Filesystem::of(&tree.path().join("b")).unwrap();
+
+    assert_eq!(a.device, b.device);
+    assert!(a.available > 0);
+  }
+}
@@ -0,0 +1,9 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub(crate) enum Format {
+  Json,
+  Ndjson,
+  #[default]
+  Text,
+}
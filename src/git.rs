@@ -0,0 +1,515 @@
+use super::*;
+
+/// A git work tree and the paths tracked in its index.
+#[derive(Debug)]
+pub(crate) struct Repository {
+  paths: Vec<Vec<u8>>,
+  root: PathBuf,
+}
+
+impl Repository {
+  /// Every path at or below `path` with uncommitted changes.
+  pub(crate) fn changes(path: &Path) -> Result<Vec<PathBuf>> {
+    let path = std::path::absolute(path)?;
+
+    let Some((root, _)) = Self::discover(&path)? else {
+      return Ok(Vec::new());
+    };
+
+    let output = Command::new("git")
+      .args(["status", "--porcelain=v1", "-z", "--untracked-files=normal"])
+      .args(["--", "."])
+      .current_dir(&path)
+      .output()
+      .map_err(|error| anyhow!("failed to run `git status`: {error}"))?;
+
+    ensure!(
+      output.status.success(),
+      "`git status` failed in `{}`: {}",
+      path.display(),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+
+    Ok(Self::parse_status(&root, &output.stdout))
+  }
+
+  /// Finds the work tree root and git directory enclosing `path`.
+  fn discover(path: &Path) -> Result<Option<(PathBuf, PathBuf)>> {
+    for ancestor in path.ancestors() {
+      let dot_git = ancestor.join(".git");
+
+      let Ok(metadata) = fs::metadata(&dot_git) else {
+        continue;
+      };
+
+      if metadata.is_dir() {
+        return Ok(Some((ancestor.to_path_buf(), dot_git)));
+      }
+
+      let contents = fs::read_to_string(&dot_git)?;
+
+      let git_dir = contents
+        .trim()
+        .strip_prefix("gitdir:")
+        .ok_or_else(|| anyhow!("invalid `.git` file `{}`", dot_git.display()))?
+        .trim();
+
+      return Ok(Some((ancestor.to_path_buf(), ancestor.join(git_dir))));
+    }
+
+    Ok(None)
+  }
+
+  fn hash_length(git_dir: &Path) -> usize {
+    let common = fs::read_to_string(git_dir.join("commondir")).map_or_else(
+      |_| git_dir.to_path_buf(),
+      |commondir| git_dir.join(commondir.trim()),
+    );
+
+    let config = fs::read_to_string(common.join("config")).unwrap_or_default();
+
+    let sha256 = config.lines().any(|line| {
+      let line = line.trim().to_ascii_lowercase();
+
+      line.starts_with("objectformat") && line.ends_with("sha256")
+    });
+
+    if sha256 { 32 } else { 20 }
+  }
+
+  /// The time of the last commit touching anything at or below `path`.
+  pub(crate) fn last_commit(path: &Path) -> Result<Option<SystemTime>> {
+    let path = std::path::absolute(path)?;
+
+    if Self::discover(&path)?.is_none() {
+      return Ok(None);
+    }
+
+    let output = Command::new("git")
+      .args(["log", "-1", "--format=%ct", "--", "."])
+      .current_dir(&path)
+      .output()
+      .map_err(|error| anyhow!("failed to run `git log`: {error}"))?;
+
+    if !output.status.success() {
+      return Ok(None);
+    }
+
+    Ok(
+      String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)),
+    )
+  }
+
+  fn open(root: PathBuf, git_dir: &Path) -> Result<Self> {
+    let index = match fs::read(git_dir.join("index")) {
+      Ok(index) => index,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+      Err(error) => return Err(error.into()),
+    };
+
+    let mut paths = if index.is_empty() {
+      Vec::new()
+    } else {
+      Self::parse(&index, Self::hash_length(git_dir)).map_err(|error| {
+        error.context(format!(
+          "failed to read git index in `{}`",
+          git_dir.display()
+        ))
+      })?
+    };
+
+    paths.sort_unstable();
+
+    Ok(Self { paths, root })
+  }
+
+  /// Parses the paths out of a version 2, 3 or 4 index file.
+  fn parse(index: &[u8], hash_length: usize) -> Result<Vec<Vec<u8>>> {
+    fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+      let bytes = bytes
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("unexpected end of index"))?;
+
+      Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+      let bytes = bytes
+        .get(offset..offset + 2)
+        .ok_or_else(|| anyhow!("unexpected end of index"))?;
+
+      Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    ensure!(index.starts_with(b"DIRC"), "missing index signature");
+
+    let version = read_u32(index, 4)?;
+
+    ensure!(
+      (2..=4).contains(&version),
+      "unsupported index version {version}"
+    );
+
+    let count = read_u32(index, 8)?;
+
+    let mut paths = Vec::new();
+
+    let mut offset = 12;
+
+    let mut previous = Vec::<u8>::new();
+
+    for _ in 0..count {
+      let start = offset;
+
+      let flags_offset = start + 40 + hash_length;
+
+      let flags = read_u16(index, flags_offset)?;
+
+      offset = flags_offset + 2;
+
+      if version >= 3 && flags & 0x4000 != 0 {
+        offset += 2;
+      }
+
+      let mut path = if version == 4 {
+        let mut byte = *index
+          .get(offset)
+          .ok_or_else(|| anyhow!("unexpected end of index"))?;
+
+        offset += 1;
+
+        let mut strip = usize::from(byte & 0x7f);
+
+        while byte & 0x80 != 0 {
+          byte = *index
+            .get(offset)
+            .ok_or_else(|| anyhow!("unexpected end of index"))?;
+
+          offset += 1;
+
+          strip = ((strip + 1) << 7) | usize::from(byte & 0x7f);
+        }
+
+        ensure!(strip <= previous.len(), "invalid path prefix in index");
+
+        previous[..previous.len() - strip].to_vec()
+      } else {
+        Vec::new()
+      };
+
+      let length = index
+        .get(offset..)
+        .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+        .ok_or_else(|| anyhow!("unterminated path in index"))?;
+
+      path.extend_from_slice(&index[offset..offset + length]);
+
+      offset += length + 1;
+
+      if version < 4 {
+        while !(offset - start).is_multiple_of(8) {
+          offset += 1;
+        }
+      }
+
+      previous.clone_from(&path);
+
+      paths.push(path);
+    }
+
+    Ok(paths)
+  }
+
+  /// Parses the output of `git status --porcelain=v1 -z`.
+  fn parse_status(root: &Path, status: &[u8]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    let mut records = status.split(|byte| *byte == 0);
+
+    while let Some(record) = records.next() {
+      if record.len() < 4 {
+        continue;
+      }
+
+      paths.push(root.join(String::from_utf8_lossy(&record[3..]).as_ref()));
+
+      if matches!(record[0], b'R' | b'C') {
+        records.next();
+      }
+    }
+
+    paths
+  }
+
+  /// Whether `path`, or anything below it, is tracked in the index.
+  pub(crate) fn tracks(&self, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(&self.root) else {
+      return false;
+    };
+
+    let relative = relative
+      .components()
+      .map(|component| component.as_os_str().as_encoded_bytes())
+      .collect::<Vec<_>>()
+      .join(&b'/');
+
+    if relative.is_empty() {
+      return !self.paths.is_empty();
+    }
+
+    if self.paths.binary_search(&relative).is_ok() {
+      return true;
+    }
+
+    let mut prefix = relative;
+    prefix.push(b'/');
+
+    let start = self.paths.partition_point(|path| *path < prefix);
+
+    self
+      .paths
+      .get(start)
+      .is_some_and(|path| path.starts_with(&prefix))
+  }
+}
+
+/// Repositories opened during a run, so that each index is only read once.
+#[derive(Debug, Default)]
+pub(crate) struct Repositories {
+  repositories: Mutex<HashMap<PathBuf, Arc<Repository>>>,
+}
+
+impl Repositories {
+  /// Whether `path` is tracked by the repository above it.
+  pub(crate) fn tracks(&self, path: &Path) -> Result<bool> {
+    let path = std::path::absolute(path)?;
+
+    let Some((root, git_dir)) =
+      Repository::discover(path.parent().unwrap_or(&path))?
+    else {
+      return Ok(false);
+    };
+
+    let cached = self
+      .repositories
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(&root)
+      .cloned();
+
+    if let Some(repository) = cached {
+      return Ok(repository.tracks(&path));
+    }
+
+    let repository = Repository::open(root.clone(), &git_dir)?;
+
+    let tracks = repository.tracks(&path);
+
+    self
+      .repositories
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .insert(root, Arc::new(repository));
+
+    Ok(tracks)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  fn entry(version: u32, path: &str, strip: u8) -> Vec<u8> {
+    let mut entry = vec![0; 40 + 20];
+
+    #[allow(clippy::cast_possible_truncation)]
+    entry.extend_from_slice(&(path.len() as u16).to_be_bytes());
+
+    if version == 4 {
+      entry.push(strip);
+    }
+
+    entry.extend_from_slice(path.as_bytes());
+
+    entry.push(0);
+
+    if version < 4 {
+      while !entry.len().is_multiple_of(8) {
+        entry.push(0);
+      }
+    }
+
+    entry
+  }
+
+  fn index(version: u32, entries: &[(&str, u8)]) -> Vec<u8> {
+    let mut index = b"DIRC".to_vec();
+
+    index.extend_from_slice(&version.to_be_bytes());
+
+    #[allow(clippy::cast_possible_truncation)]
+    index.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+    for (path, strip) in entries {
+      index.extend(entry(version, path, *strip));
+    }
+
+    index
+  }
+
+  #[test]
+  fn parse_version_2() {
+    assert_eq!(
+      Repository::parse(&index(2, &[("b", 0), ("build/a", 0)]), 20).unwrap(),
+      vec![b"b".to_vec(), b"build/a".to_vec()],
+    );
+  }
+
+  #[test]
+  fn parse_version_4() {
+    assert_eq!(
+      Repository::parse(&index(4, &[("build/a", 0), ("b", 1)]), 20).unwrap(),
+      vec![b"build/a".to_vec(), b"build/b".to_vec()],
+    );
+  }
+
+  #[test]
+  fn parse_status_skips_rename_sources() {
+    assert_eq!(
+      Repository::parse_status(
+        Path::new("/repo"),
+        b" M src/main.rs\0R  new.rs\0old.rs\0?? target/\0",
+      ),
+      vec![
+        PathBuf::from("/repo/src/main.rs"),
+        PathBuf::from("/repo/new.rs"),
+        PathBuf::from("/repo/target/"),
+      ],
+    );
+  }
+
+  #[test]
+  fn hash_length_reads_worktree_common_config() {
+    let tree = temptree! {
+      ".git": {
+        "config": "[extensions]\n\tobjectFormat = sha256\n",
+        "worktrees": {
+          "feature": {
+            "commondir": "../..\n",
+          },
+        },
+      },
+    };
+
+    let git_dir = tree.path().join(".git");
+
+    assert_eq!(Repository::hash_length(&git_dir), 32);
+
+    assert_eq!(
+      Repository::hash_length(&git_dir.join("worktrees/feature")),
+      32
+    );
+
+    assert_eq!(Repository::hash_length(tree.path()), 20);
+  }
+
+  #[test]
+  fn parse_rejects_invalid_signature() {
+    assert_eq!(
+      Repository::parse(b"NOPE", 20).unwrap_err().to_string(),
+      "missing index signature",
+    );
+  }
+
+  #[test]
+  fn tracks_paths_and_their_ancestors() {
+    let repository = Repository {
+      paths: vec![
+        b"build-tools/run".to_vec(),
+        b"build/gen/a".to_vec(),
+        b"readme".to_vec(),
+      ],
+      root: PathBuf::from("/repo"),
+    };
+
+    assert!(repository.tracks(Path::new("/repo/build")));
+    assert!(repository.tracks(Path::new("/repo/build/gen")));
+    assert!(repository.tracks(Path::new("/repo/readme")));
+    assert!(!repository.tracks(Path::new("/repo/buil")));
+    assert!(!repository.tracks(Path::new("/repo/target")));
+    assert!(!repository.tracks(Path::new("/elsewhere/build")));
+  }
+
+  #[test]
+  fn tracks_finds_enclosing_repository() {
+    let tree = temptree! {
+      ".git": {},
+      "project": {
+        "build": {},
+      },
+    };
+
+    fs::write(
+      tree.path().join(".git/index"),
+      index(2, &[("project/build/keep", 0)]),
+    )
+    .unwrap();
+
+    let repositories = Repositories::default();
+
+    assert!(
+      repositories
+        .tracks(&tree.path().join("project/build"))
+        .unwrap()
+    );
+
+    assert!(
+      !repositories
+        .tracks(&tree.path().join("project/target"))
+        .unwrap()
+    );
+  }
+
+  #[test]
+  fn tracks_follows_git_files() {
+    let tree = temptree! {
+      "modules": {
+        "project": {},
+      },
+      "project": {
+        ".git": "gitdir: ../modules/project",
+      },
+    };
+
+    fs::write(
+      tree.path().join("modules/project/index"),
+      index(2, &[("build/keep", 0)]),
+    )
+    .unwrap();
+
+    assert!(
+      Repositories::default()
+        .tracks(&tree.path().join("project/build"))
+        .unwrap()
+    );
+  }
+
+  #[test]
+  fn paths_outside_repositories_are_untracked() {
+    let tree = temptree! {
+      "project": {
+        "build": {},
+      },
+    };
+
+    assert!(
+      !Repositories::default()
+        .tracks(&tree.path().join("project/build"))
+        .unwrap()
+    );
+  }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+/// Safety checks that can veto removals.
+#[derive(Debug, Default)]
+pub(crate) struct Guard {
+  pub(crate) allow_tracked: bool,
+  pub(crate) exclude: Exclude,
+  pub(crate) min_size: Option<Bytes>,
+  pub(crate) newer_than: Option<Cutoff>,
+  pub(crate) older_than: Option<Cutoff>,
+  pub(crate) processes: Option<Processes>,
+  pub(crate) repositories: Repositories,
+  pub(crate) skip_dirty: bool,
+}
+
+impl Guard {
+  /// Returns why a project should be left alone entirely, if it should.
+  pub(crate) fn check(
+    &self,
+    context: &Context,
+    reports: &[Report],
+  ) -> Result<Option<Reason>> {
+    if self.exclude.excludes(&context.root) {
+      return Ok(Some(Reason::Excluded));
+    }
+
+    if let Some(min_size) = self.min_size {
+      let sizes = reports
+        .iter()
+        .flat_map(|report| &report.tasks)
+        .filter_map(|task| match task {
+          Task::Remove { size, .. } => Some(*size),
+          Task::Command(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+      if !sizes.is_empty() && Bytes(sizes.iter().sum()) < min_size {
+        return Ok(Some(Reason::Small));
+      }
+    }
+
+    if let Some(processes) = &self.processes
+      && processes.active(&context.root)?
+    {
+      return Ok(Some(Reason::Active));
+    }
+
+    if self.skip_dirty {
+      let removals = reports
+        .iter()
+        .flat_map(|report| &report.tasks)
+        .filter_map(|task| match task {
+          Task::Remove { path, .. } => {
+            std::path::absolute(context.root.join(path)).ok()
+          }
+          Task::Command(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+      let changes = match Repository::changes(&context.root) {
+        Ok(changes) => changes,
+        Err(error) => return Ok(Some(Reason::Unchecked(error.to_string()))),
+      };
+
+      let dirty = changes
+        .iter()
+        .any(|change| !removals.iter().any(|path| change.starts_with(path)));
+
+      if dirty {
+        return Ok(Some(Reason::Dirty));
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// Whether anything below `path`, already measured with `usage`, matches
+  /// an exclude pattern.
+  pub(crate) fn contains_excluded(&self, path: &Path, usage: &Usage) -> bool {
+    self.exclude.contains(path) || usage.contains_excluded(path)
+  }
+
+  /// Whether the project's age falls within the age filters.
+  pub(crate) fn due(&self, rule: &dyn Rule, context: &Context) -> bool {
+    let older_than = rule.older_than().or(self.older_than);
+
+    if older_than.is_none() && self.newer_than.is_none() {
+      return true;
+    }
+
+    context.modified_time(rule).is_ok_and(|modified| {
+      older_than.is_none_or(|cutoff| cutoff.older_than(modified))
+        && self
+          .newer_than
+          .is_none_or(|cutoff| cutoff.newer_than(modified))
+    })
+  }
+
+  /// Whether `path` matches an exclude pattern.
+  pub(crate) fn excluded(&self, path: &Path) -> bool {
+    self.exclude.excludes(path)
+  }
+
+  /// Whether a path of `size` bytes is too small for `rule` to bother with.
+  pub(crate) fn small(&self, rule: &dyn Rule, size: u64) -> bool {
+    rule
+      .min_size()
+      .or(self.min_size)
+      .is_some_and(|min_size| Bytes(size) < min_size)
+  }
+
+  /// Whether removing `path` should be refused because git tracks it.
+  pub(crate) fn tracked(&self, path: &Path) -> Result<bool> {
+    Ok(!self.allow_tracked && self.repositories.tracks(path)?)
+  }
+}
@@ -0,0 +1,652 @@
+use super::*;
+
+#[derive(Debug)]
+pub(crate) struct Entry {
+  pub(crate) directory: bool,
+  pub(crate) path: PathBuf,
+  /// Depth of the innermost remove target above this entry.
+  pub(crate) target: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Index {
+  pub(crate) age_source: AgeSource,
+  detections: GlobSet,
+  entries: Vec<Entry>,
+  follow_symlinks: bool,
+  max_depth: Option<usize>,
+  patterns: HashMap<&'static str, usize>,
+  pub(crate) root: PathBuf,
+  targets: GlobSet,
+}
+
+impl Index {
+  /// Whether a project above `directory` has a rule that removes it.
+  fn claimed(
+    &self,
+    directory: &Path,
+    rules: &[Box<dyn Rule>],
+    targets: &[GlobSet],
+    claims: &mut HashMap<(PathBuf, usize), bool>,
+  ) -> Result<bool> {
+    for ancestor in directory.ancestors().skip(1) {
+      let suffix = directory.strip_prefix(ancestor)?;
+
+      let mut context = None;
+
+      for (index, (rule, targets)) in rules.iter().zip(targets).enumerate() {
+        if !targets.is_match(suffix) {
+          continue;
+        }
+
+        let key = (ancestor.to_path_buf(), index);
+
+        let claimed = if let Some(claimed) = claims.get(&key) {
+          *claimed
+        } else {
+          let context = match &mut context {
+            Some(context) => context,
+            None => context.insert(self.context(ancestor)?),
+          };
+
+          let claimed = context.local.enabled(rule.as_ref())
+            && rule.detection().matches(context);
+
+          claims.insert(key, claimed);
+
+          claimed
+        };
+
+        if claimed {
+          return Ok(true);
+        }
+      }
+    }
+
+    Ok(false)
+  }
+
+  fn contains(&self, path: &Path) -> bool {
+    self
+      .entries
+      .binary_search_by(|entry| entry.path.as_path().cmp(path))
+      .is_ok()
+  }
+
+  pub(crate) fn context(&self, relative: &Path) -> Result<Context<'_>> {
+    let root = if relative.as_os_str().is_empty() {
+      self.root.clone()
+    } else {
+      self.root.join(relative)
+    };
+
+    let local = if self.contains(&relative.join(Local::FILENAME)) {
+      Local::load(&root.join(Local::FILENAME))
+        .unwrap_or_else(|error| Local::invalid(&error))
+    } else {
+      Local::default()
+    };
+
+    Ok(Context {
+      commit: OnceLock::new(),
+      detected: OnceLock::new(),
+      follow_symlinks: self.follow_symlinks,
+      index: self,
+      local,
+      manifests: Mutex::default(),
+      relative: relative.to_path_buf(),
+      root,
+      times: OnceLock::new(),
+    })
+  }
+
+  /// Returns every entry strictly below `relative`.
+  pub(crate) fn descendants(&self, relative: &Path) -> &[Entry] {
+    if relative.as_os_str().is_empty() {
+      return &self.entries;
+    }
+
+    let start = self
+      .entries
+      .partition_point(|entry| entry.path.as_path() <= relative);
+
+    let length = self.entries[start..]
+      .partition_point(|entry| entry.path.starts_with(relative));
+
+    &self.entries[start..start + length]
+  }
+
+  /// The detection patterns matching, relative to `relative`, some entry
+  /// below `within`.
+  pub(crate) fn detected(
+    &self,
+    relative: &Path,
+    within: &Path,
+  ) -> HashSet<usize> {
+    let depth = relative.components().count();
+
+    let (mut detected, mut matches) = (HashSet::new(), Vec::new());
+
+    for entry in self
+      .descendants(within)
+      .iter()
+      .filter(|entry| entry.target.is_none_or(|target| target <= depth))
+    {
+      self.detections.matches_into(
+        entry.path.strip_prefix(relative).unwrap_or(&entry.path),
+        &mut matches,
+      );
+
+      detected.extend(matches.iter().copied());
+    }
+
+    detected
+  }
+
+  pub(crate) fn directories(&self) -> impl Iterator<Item = &Path> {
+    once(Path::new("")).chain(
+      self
+        .entries
+        .iter()
+        .filter(|entry| entry.directory)
+        .map(|entry| entry.path.as_path()),
+    )
+  }
+
+  /// Whether a project within the maximum depth is detected above
+  /// `directory`, which then has to be walked to measure and date it.
+  fn inside_project(
+    &self,
+    directory: &Path,
+    rules: &[Box<dyn Rule>],
+    projects: &mut HashMap<PathBuf, bool>,
+  ) -> Result<bool> {
+    for ancestor in directory.ancestors().skip(1) {
+      if self
+        .max_depth
+        .is_some_and(|max_depth| ancestor.components().count() > max_depth)
+      {
+        continue;
+      }
+
+      let project = if let Some(project) = projects.get(ancestor) {
+        *project
+      } else {
+        let context = self.context(ancestor)?;
+
+        let project = context
+          .rules(rules)
+          .any(|rule| rule.detection().matches(&context));
+
+        projects.insert(ancestor.to_path_buf(), project);
+
+        project
+      };
+
+      if project {
+        return Ok(true);
+      }
+    }
+
+    Ok(false)
+  }
+
+  pub(crate) fn is_target(path: &Path, targets: &GlobSet) -> bool {
+    path
+      .ancestors()
+      .skip(1)
+      .filter_map(|ancestor| path.strip_prefix(ancestor).ok())
+      .any(|suffix| targets.is_match(suffix))
+  }
+
+  pub(crate) fn new(
+    root: PathBuf,
+    age_source: AgeSource,
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+    rules: &[Box<dyn Rule>],
+  ) -> Result<Self> {
+    let targets = rules
+      .iter()
+      .map(|rule| Self::targets(once(rule.as_ref())))
+      .collect::<Result<Vec<_>>>()?;
+
+    let mut patterns = HashMap::new();
+
+    let mut detections = GlobSetBuilder::new();
+
+    for pattern in once(Local::FILENAME)
+      .chain(rules.iter().flat_map(|rule| rule.detection().patterns()))
+    {
+      if !patterns.contains_key(pattern) {
+        patterns.insert(pattern, patterns.len());
+        detections.add(Glob::new(pattern)?);
+      }
+    }
+
+    let mut index = Self {
+      age_source,
+      detections: detections.build()?,
+      entries: Vec::new(),
+      follow_symlinks,
+      max_depth,
+      patterns,
+      root,
+      targets: Self::targets(rules.iter().map(AsRef::as_ref))?,
+    };
+
+    let (entries, mut pending, mut deferred) =
+      index.walk(Path::new(""), max_depth)?;
+
+    index.entries = entries;
+
+    let mut claims = HashMap::new();
+
+    let mut projects = HashMap::new();
+
+    loop {
+      while !pending.is_empty() {
+        let mut unclaimed = Vec::new();
+
+        for directory in pending {
+          if !index.claimed(&directory, rules, &targets, &mut claims)? {
+            unclaimed.push(directory);
+          }
+        }
+
+        pending = Vec::new();
+
+        for directory in unclaimed {
+          if max_depth
+            .is_some_and(|max_depth| directory.components().count() > max_depth)
+          {
+            deferred.push(directory);
+            continue;
+          }
+
+          let (entries, targets, below) = index.walk(&directory, max_depth)?;
+          index.entries.extend(entries);
+          pending.extend(targets);
+          deferred.extend(below);
+        }
+
+        index.entries.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+      }
+
+      let mut inside = Vec::new();
+
+      for directory in deferred.drain(..) {
+        if index.inside_project(&directory, rules, &mut projects)? {
+          inside.push(directory);
+        }
+      }
+
+      if inside.is_empty() {
+        break;
+      }
+
+      for directory in inside {
+        let (entries, targets, _) = index.walk(&directory, None)?;
+        index.entries.extend(entries);
+        pending.extend(targets);
+      }
+
+      index.entries.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    Ok(index)
+  }
+
+  /// The position of `pattern` among the detection patterns.
+  pub(crate) fn pattern(&self, pattern: &str) -> Option<usize> {
+    self.patterns.get(pattern).copied()
+  }
+
+  pub(crate) fn targets<'r>(
+    rules: impl IntoIterator<Item = &'r dyn Rule>,
+  ) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for rule in rules {
+      for action in rule.actions() {
+        if let Action::Remove(pattern) = action {
+          builder.add(Glob::new(pattern)?);
+        }
+      }
+    }
+
+    Ok(builder.build()?)
+  }
+
+  /// Walks `directory`, setting aside the remove targets found below it and
+  /// the directories deeper than `max_depth`.
+  fn walk(
+    &self,
+    directory: &Path,
+    max_depth: Option<usize>,
+  ) -> Result<(Vec<Entry>, Vec<PathBuf>, Vec<PathBuf>)> {
+    let (mut entries, mut targets, mut deferred) =
+      (Vec::new(), Vec::new(), Vec::new());
+
+    let target = (!directory.as_os_str().is_empty())
+      .then(|| directory.components().count());
+
+    let mut walker = WalkDir::new(self.root.join(directory))
+      .follow_links(self.follow_symlinks)
+      .sort_by_file_name()
+      .min_depth(1);
+
+    if let Some(max_depth) = max_depth {
+      walker = walker.max_depth(max_depth + 1 - directory.components().count());
+    }
+
+    let mut walker = walker.into_iter();
+
+    while let Some(entry) = walker.next() {
+      let entry = entry?;
+
+      if entry.file_type().is_dir() && entry.file_name() == Trash::DIRECTORY {
+        walker.skip_current_dir();
+        continue;
+      }
+
+      let path = entry
+        .path()
+        .strip_prefix(&self.root)
+        .unwrap_or(entry.path())
+        .to_path_buf();
+
+      let directory = entry.file_type().is_dir();
+
+      entries.push(Entry {
+        directory,
+        path: path.clone(),
+        target,
+      });
+
+      if directory && Self::is_target(&path, &self.targets) {
+        walker.skip_current_dir();
+        targets.push(path);
+      } else if directory
+        && max_depth
+          .is_some_and(|max_depth| path.components().count() > max_depth)
+      {
+        deferred.push(path);
+      }
+    }
+
+    Ok((entries, targets, deferred))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  fn paths(entries: &[Entry]) -> Vec<PathBuf> {
+    entries.iter().map(|entry| entry.path.clone()).collect()
+  }
+
+  #[test]
+  fn descendants_of_root() {
+    let tree = temptree! {
+      "b": {
+        "file": "",
+      },
+      "a": "",
+    };
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+      paths(index.descendants(Path::new(""))),
+      vec![
+        PathBuf::from("a"),
+        PathBuf::from("b"),
+        PathBuf::from("b/file"),
+      ],
+    );
+  }
+
+  #[test]
+  fn descendants_exclude_siblings_with_shared_prefix() {
+    let tree = temptree! {
+      "a": {
+        "inner": {
+          "file": "",
+        },
+      },
+      "a b": {
+        "file": "",
+      },
+      "a.txt": "",
+    };
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+      paths(index.descendants(Path::new("a"))),
+      vec![PathBuf::from("a/inner"), PathBuf::from("a/inner/file")],
+    );
+  }
+
+  #[test]
+  fn descendants_of_file_is_empty() {
+    let tree = temptree! {
+      "file": "",
+    };
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    assert!(index.descendants(Path::new("file")).is_empty());
+  }
+
+  #[test]
+  fn targets_are_not_descended_into() {
+    let tree = temptree! {
+      "project": {
+        "App.csproj": "",
+        "bin": {
+          "Debug": {
+            "App.dll": "",
+          },
+        },
+      },
+    };
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &rules,
+    )
+    .unwrap();
+
+    assert_eq!(
+      paths(index.descendants(Path::new(""))),
+      vec![
+        PathBuf::from("project"),
+        PathBuf::from("project/App.csproj"),
+        PathBuf::from("project/bin"),
+      ],
+    );
+  }
+
+  #[test]
+  fn unclaimed_targets_are_descended_into() {
+    let tree = temptree! {
+      "build": {
+        "app": {
+          "Cargo.toml": "",
+          "target": {
+            "debug": "",
+          },
+        },
+      },
+    };
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &rules,
+    )
+    .unwrap();
+
+    assert_eq!(
+      paths(index.descendants(Path::new(""))),
+      vec![
+        PathBuf::from("build"),
+        PathBuf::from("build/app"),
+        PathBuf::from("build/app/Cargo.toml"),
+        PathBuf::from("build/app/target"),
+      ],
+    );
+  }
+
+  #[test]
+  fn targets_match_relative_to_any_ancestor() {
+    let targets = GlobSetBuilder::new()
+      .add(Glob::new("linux/flutter/ephemeral").unwrap())
+      .add(Glob::new("**/target").unwrap())
+      .build()
+      .unwrap();
+
+    assert!(Index::is_target(
+      Path::new("app/linux/flutter/ephemeral"),
+      &targets
+    ));
+
+    assert!(Index::is_target(Path::new("a/b/target"), &targets));
+
+    assert!(!Index::is_target(Path::new("app/linux/flutter"), &targets));
+  }
+
+  #[test]
+  fn detected_patterns_are_relative_to_the_directory() {
+    let tree = temptree! {
+      "workspace": {
+        "Cargo.toml": "",
+        "web": {
+          "package.json": "",
+        },
+      },
+    };
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &rules,
+    )
+    .unwrap();
+
+    let detected =
+      index.detected(Path::new("workspace"), Path::new("workspace"));
+
+    let cargo = index.pattern("Cargo.toml").unwrap();
+    let node = index.pattern("package.json").unwrap();
+
+    assert!(detected.contains(&cargo));
+    assert!(!detected.contains(&node));
+
+    assert!(
+      index
+        .detected(Path::new("workspace/web"), Path::new("workspace/web"))
+        .contains(&node)
+    );
+  }
+
+  #[test]
+  fn directories_include_root() {
+    let tree = temptree! {
+      "b": {
+        "c": {},
+      },
+      "a": "",
+    };
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+      index.directories().collect::<Vec<_>>(),
+      vec![Path::new(""), Path::new("b"), Path::new("b/c")],
+    );
+  }
+
+  #[test]
+  fn max_depth_only_descends_into_projects() {
+    let tree = temptree! {
+      "org": {
+        "team": {
+          "repo": {
+            "Cargo.toml": "",
+            "src": {
+              "main.rs": "",
+            },
+          },
+        },
+        "web": {
+          "package.json": "",
+          "src": {
+            "lib": {
+              "index.js": "",
+            },
+          },
+        },
+      },
+    };
+
+    let rules: Vec<Box<dyn Rule>> = Config::default().try_into().unwrap();
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      Some(2),
+      &rules,
+    )
+    .unwrap();
+
+    assert!(index.contains(Path::new("org/team/repo")));
+    assert!(!index.contains(Path::new("org/team/repo/Cargo.toml")));
+    assert!(index.contains(Path::new("org/web/src/lib/index.js")));
+  }
+}
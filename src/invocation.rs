@@ -0,0 +1,349 @@
+use super::*;
+
+/// Kills `child` and, on Unix, every other process in its group.
+fn kill(child: &mut Child) -> io::Result<()> {
+  #[cfg(unix)]
+  {
+    let pid = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+
+    // SAFETY: `kill` takes no pointers, and `-pid` names the process group
+    // the child was made the leader of when it was spawned.
+    if unsafe { libc::kill(-pid, libc::SIGKILL) } == 0 {
+      return Ok(());
+    }
+
+    match io::Error::last_os_error() {
+      error if error.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+      error => Err(error),
+    }
+  }
+
+  #[cfg(not(unix))]
+  child.kill()
+}
+
+/// A command run by a rule, along with how to run it.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Invocation {
+  pub(crate) command: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) cwd: Option<PathBuf>,
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub(crate) env: BTreeMap<String, String>,
+  #[serde(default)]
+  pub(crate) output: OutputMode,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) shell: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) timeout: Option<Age>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) tool: Option<String>,
+}
+
+impl Display for Invocation {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.command.trim())
+  }
+}
+
+impl Invocation {
+  /// Whether the tool the command needs, if any, can be found.
+  pub(crate) fn available(&self, root: &Path) -> bool {
+    let Some(tool) = self.tool.as_deref() else {
+      return true;
+    };
+
+    if Path::new(tool).components().count() > 1 {
+      return Self::executable(&root.join(tool));
+    }
+
+    let extensions = if cfg!(windows) {
+      env::var("PATHEXT")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::to_owned)
+        .chain(once(String::new()))
+        .collect()
+    } else {
+      vec![String::new()]
+    };
+
+    env::var_os("PATH").is_some_and(|paths| {
+      env::split_paths(&paths).any(|directory| {
+        extensions.iter().any(|extension| {
+          Self::executable(&directory.join(format!("{tool}{extension}")))
+        })
+      })
+    })
+  }
+
+  fn build(&self, root: &Path) -> Command {
+    let mut command = match self.shell.as_deref() {
+      Some([program, arguments @ ..]) => {
+        let mut command = Command::new(program);
+        command.args(arguments);
+        command
+      }
+      _ if cfg!(windows) => {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+      }
+      _ => {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+      }
+    };
+
+    command
+      .arg(self.command.trim())
+      .current_dir(match &self.cwd {
+        Some(cwd) => root.join(cwd),
+        None => root.to_path_buf(),
+      })
+      .envs(&self.env);
+
+    match self.output {
+      OutputMode::Capture => {
+        command
+          .stdin(Stdio::null())
+          .stdout(Stdio::piped())
+          .stderr(Stdio::piped());
+      }
+      // Streamed output goes to stderr, keeping stdout for the report.
+      OutputMode::Stream => {
+        command.stdout(io::stderr());
+      }
+    }
+
+    // A command with a timeout gets its own process group, so that anything
+    // it spawns can be killed along with it.
+    #[cfg(unix)]
+    if self.timeout.is_some() {
+      use std::os::unix::process::CommandExt;
+      command.stdin(Stdio::null()).process_group(0);
+    }
+
+    command
+  }
+
+  /// Reads `pipe` to the end on its own thread.
+  fn drain(
+    pipe: Option<impl Read + Send + 'static>,
+  ) -> Option<thread::JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+      thread::spawn(move || {
+        let mut buffer = Vec::new();
+        pipe.read_to_end(&mut buffer).ok();
+        buffer
+      })
+    })
+  }
+
+  fn executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+
+      fs::metadata(path).is_ok_and(|metadata| {
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+      })
+    }
+
+    #[cfg(not(unix))]
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_file())
+  }
+
+  /// Runs the command in `root`, returning its output unless it was streamed.
+  pub(crate) fn run(&self, root: &Path) -> Result<Option<String>> {
+    let text = self.command.trim();
+
+    ensure!(!text.is_empty(), "command action cannot be empty");
+
+    let mut child = self.build(root).spawn().map_err(|error| {
+      anyhow!(
+        "failed to run command `{text}` in `{}`: {error}",
+        root.display()
+      )
+    })?;
+
+    let stdout = Self::drain(child.stdout.take());
+    let stderr = Self::drain(child.stderr.take());
+
+    let status = match self.timeout {
+      Some(timeout) => {
+        let deadline = Instant::now() + timeout.0;
+
+        loop {
+          if let Some(status) = child.try_wait()? {
+            break Ok(status);
+          }
+
+          if Instant::now() >= deadline {
+            kill(&mut child)?;
+            child.wait()?;
+            break Err(timeout);
+          }
+
+          thread::sleep(Duration::from_millis(10));
+        }
+      }
+      None => Ok(child.wait()?),
+    };
+
+    let output = (self.output == OutputMode::Capture).then(|| {
+      [stdout, stderr]
+        .into_iter()
+        .flatten()
+        .map(|reader| reader.join().unwrap_or_default())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .collect::<String>()
+    });
+
+    let details = output
+      .as_deref()
+      .map(str::trim_end)
+      .filter(|output| !output.is_empty())
+      .map(|output| format!(":\n{output}"))
+      .unwrap_or_default();
+
+    match status {
+      Ok(status) => ensure!(
+        status.success(),
+        "command `{text}` failed in `{}` with {status}{details}",
+        root.display()
+      ),
+      Err(timeout) => bail!(
+        "command `{text}` timed out after {timeout} in `{}`{details}",
+        root.display()
+      ),
+    }
+
+    Ok(output)
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use {
+    super::*, std::os::unix::fs::PermissionsExt, tempfile::tempdir,
+    temptree::temptree,
+  };
+
+  fn invocation(command: &str) -> Invocation {
+    Invocation {
+      command: command.into(),
+      ..Invocation::default()
+    }
+  }
+
+  #[test]
+  fn tools_are_looked_up_on_path_or_relative_to_the_root() {
+    let tree = temptree! {
+      "gradlew": "",
+    };
+
+    let tool = |tool: &str| Invocation {
+      tool: Some(tool.into()),
+      ..invocation("true")
+    };
+
+    assert!(invocation("true").available(tree.path()));
+    assert!(tool("sh").available(tree.path()));
+    assert!(!tool("swab-missing-tool").available(tree.path()));
+    assert!(!tool("./gradlew").available(tree.path()));
+
+    let gradlew = tree.path().join("gradlew");
+
+    fs::set_permissions(&gradlew, fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert!(tool("./gradlew").available(tree.path()));
+  }
+
+  #[test]
+  fn captures_output() {
+    let tempdir = tempdir().unwrap();
+
+    assert_eq!(
+      invocation("echo out; echo err >&2")
+        .run(tempdir.path())
+        .unwrap(),
+      Some("out\nerr\n".into()),
+    );
+  }
+
+  #[test]
+  fn streamed_output_is_not_captured() {
+    let tempdir = tempdir().unwrap();
+
+    let invocation = Invocation {
+      output: OutputMode::Stream,
+      ..invocation("true")
+    };
+
+    assert_eq!(invocation.run(tempdir.path()).unwrap(), None);
+  }
+
+  #[test]
+  fn failures_include_output() {
+    let tempdir = tempdir().unwrap();
+
+    let error = invocation("echo oops; exit 3")
+      .run(tempdir.path())
+      .unwrap_err()
+      .to_string();
+
+    assert_eq!(
+      error,
+      format!(
+        "command `echo oops; exit 3` failed in `{}` with exit status: 3:\noops",
+        tempdir.path().display()
+      ),
+    );
+  }
+
+  #[test]
+  fn env_cwd_and_shell() {
+    let tempdir = tempdir().unwrap();
+
+    fs::create_dir(tempdir.path().join("sub")).unwrap();
+
+    let invocation = Invocation {
+      cwd: Some("sub".into()),
+      env: BTreeMap::from([("GREETING".into(), "hello".into())]),
+      shell: Some(vec!["sh".into(), "-ec".into()]),
+      ..invocation("echo $GREETING; pwd")
+    };
+
+    let output = invocation.run(tempdir.path()).unwrap().unwrap();
+
+    assert!(output.starts_with("hello\n"));
+    assert!(output.trim_end().ends_with("sub"));
+  }
+
+  #[test]
+  fn timeouts_kill_the_process_group() {
+    let tempdir = tempdir().unwrap();
+
+    let invocation = Invocation {
+      timeout: Some(Age(Duration::from_millis(200))),
+      ..invocation("(sleep 1; touch late) & echo started; sleep 30")
+    };
+
+    let start = Instant::now();
+
+    let error = invocation.run(tempdir.path()).unwrap_err().to_string();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    assert!(error.starts_with("command `"), "{error}");
+    assert!(error.contains("timed out"), "{error}");
+    assert!(error.ends_with(":\nstarted"), "{error}");
+
+    thread::sleep(Duration::from_millis(1500));
+
+    assert!(!tempdir.path().join("late").exists());
+  }
+}
@@ -0,0 +1,75 @@
+use super::*;
+
+/// Globs, relative to a project root, naming paths that must not be removed.
+#[derive(Debug, Default)]
+pub(crate) struct Keep {
+  globs: GlobSet,
+  literals: Vec<PathBuf>,
+}
+
+impl Keep {
+  /// Whether removing `path` would remove something kept.
+  pub(crate) fn keeps(&self, path: &Path) -> bool {
+    path
+      .ancestors()
+      .filter(|ancestor| !ancestor.as_os_str().is_empty())
+      .any(|ancestor| self.globs.is_match(ancestor))
+      || self
+        .literals
+        .iter()
+        .any(|literal| literal.starts_with(path))
+  }
+
+  pub(crate) fn new<T: AsRef<str>>(patterns: &[T]) -> Result<Self> {
+    let globs = patterns
+      .iter()
+      .map(AsRef::as_ref)
+      .try_fold(GlobSetBuilder::new(), |mut builder, pattern| {
+        builder.add(Glob::new(pattern).map_err(|error| {
+          anyhow!("invalid keep pattern `{pattern}`: {error}")
+        })?);
+        Ok::<_, Error>(builder)
+      })?
+      .build()?;
+
+    Ok(Self {
+      globs,
+      literals: patterns
+        .iter()
+        .map(|pattern| PathBuf::from(pattern.as_ref()))
+        .collect(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keeps_matching_paths_and_their_contents() {
+    let keep = Keep::new(&["build"]).unwrap();
+
+    assert!(keep.keeps(Path::new("build")));
+    assert!(keep.keeps(Path::new("build/generated")));
+    assert!(!keep.keeps(Path::new("dist")));
+  }
+
+  #[test]
+  fn keeps_parents_of_literal_patterns() {
+    let keep = Keep::new(&["vendor/foo/target"]).unwrap();
+
+    assert!(keep.keeps(Path::new("vendor")));
+    assert!(keep.keeps(Path::new("vendor/foo/target")));
+    assert!(!keep.keeps(Path::new("target")));
+    assert!(!keep.keeps(Path::new("vendor/bar/target")));
+  }
+
+  #[test]
+  fn globs_match_nested_paths() {
+    let keep = Keep::new(&["**/fixtures/**"]).unwrap();
+
+    assert!(keep.keeps(Path::new("tests/fixtures/target")));
+    assert!(!keep.keeps(Path::new("target")));
+  }
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+/// Overrides from a project's `.swab.toml`.
+#[derive(Debug, Default)]
+pub(crate) struct Local {
+  disabled: HashSet<String>,
+  /// Why the `.swab.toml` couldn't be loaded, if it couldn't.
+  pub(crate) invalid: Option<String>,
+  pub(crate) keep: Keep,
+  rule: Option<CustomRule>,
+  pub(crate) skip: bool,
+}
+
+impl Local {
+  pub(crate) const FILENAME: &'static str = ".swab.toml";
+
+  pub(crate) fn enabled(&self, rule: &dyn Rule) -> bool {
+    !self.disabled.contains(rule.id())
+  }
+
+  /// Stands in for a `.swab.toml` that can't be loaded.
+  pub(crate) fn invalid(error: &Error) -> Self {
+    Self {
+      invalid: Some(error.to_string()),
+      skip: true,
+      ..Self::default()
+    }
+  }
+
+  pub(crate) fn load(path: &Path) -> Result<Self> {
+    let contents = fs::read_to_string(path)?;
+
+    let config = toml::from_str::<ProjectConfig>(&contents)
+      .map_err(|error| anyhow!("{}", error.message()))?;
+
+    Self::try_from(config)
+  }
+
+  /// A rule holding the project's extra `remove` patterns, if it has any.
+  pub(crate) fn rule(&self) -> Option<&dyn Rule> {
+    self.rule.as_ref().map(|rule| rule as &dyn Rule)
+  }
+}
+
+impl TryFrom<ProjectConfig> for Local {
+  type Error = Error;
+
+  fn try_from(config: ProjectConfig) -> Result<Self> {
+    let keep = Keep::new(&config.keep)?;
+
+    let rule = if config.remove.is_empty() {
+      None
+    } else {
+      Some(CustomRule::try_from(RuleConfig {
+        actions: config
+          .remove
+          .into_iter()
+          .map(|remove| ConfigAction::Remove { remove })
+          .collect(),
+        allow_tracked: false,
+        detection: ConfigDetection::Pattern(Self::FILENAME.into()),
+        id: "local".into(),
+        keep: Vec::new(),
+        min_size: None,
+        name: Some("Local".into()),
+        older_than: None,
+        tags: Vec::new(),
+      })?)
+    };
+
+    Ok(Self {
+      disabled: config.disabled.into_iter().collect(),
+      invalid: None,
+      keep,
+      rule,
+      skip: config.skip,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn local(config: &str) -> Local {
+    toml::from_str::<ProjectConfig>(config)
+      .unwrap()
+      .try_into()
+      .unwrap()
+  }
+
+  #[test]
+  fn keep_patterns_are_loaded() {
+    assert!(local("keep = ['build']").keep.keeps(Path::new("build")));
+  }
+
+  #[test]
+  fn extra_removes_become_a_rule() {
+    let local = local("remove = ['generated']");
+
+    let rule = local.rule().unwrap();
+
+    assert_eq!(rule.id(), "local");
+
+    assert!(matches!(rule.actions(), [Action::Remove("generated")]));
+  }
+
+  #[test]
+  fn unknown_fields_are_rejected() {
+    assert!(toml::from_str::<ProjectConfig>("kep = ['build']").is_err());
+  }
+}
@@ -1,52 +1,128 @@
 use {
   action::Action,
   age::Age,
-  anyhow::{Error, anyhow, bail, ensure},
+  age_source::AgeSource,
+  anyhow::{Context as _, Error, anyhow, bail, ensure},
   arguments::Arguments,
   bytes::Bytes,
-  clap::Parser,
-  config::{Config, ConfigAction, ConfigDetection},
+  clap::{Args, Parser, ValueEnum},
+  config::{
+    Config, ConfigAction, ConfigDetection, CustomRule, ProjectConfig,
+    RuleConfig, TrashConfig,
+  },
   context::Context,
+  cutoff::Cutoff,
   detection::Detection,
   dialoguer::{Confirm, theme::ColorfulTheme},
-  globset::Glob,
+  discovery::Discovery,
+  etcetera::{AppStrategy, AppStrategyArgs},
+  exclude::Exclude,
+  expression::Expression,
+  failure::Failure,
+  filesystem::Filesystem,
+  format::Format,
+  git::{Repositories, Repository},
+  globset::{Glob, GlobSet, GlobSetBuilder},
+  guard::Guard,
+  index::Index,
+  invocation::Invocation,
+  keep::Keep,
+  local::Local,
+  manifest::Manifest,
+  output_mode::OutputMode,
   path_ext::PathExt,
+  plan::Plan,
+  processes::Processes,
+  project::Project,
+  rayon::{Scope, ThreadPoolBuilder, prelude::*},
+  reason::Reason,
+  record::{Document, Record},
+  regex::Regex,
   report::Report,
   rule::Rule,
-  serde::{Deserialize, Serialize},
+  selection::Selection,
+  selector::Selector,
+  serde::{
+    Deserialize, Deserializer, Serialize, Serializer, de, ser::SerializeStruct,
+  },
+  skipped::Skipped,
+  sort::Sort,
   std::{
     backtrace::BacktraceStatus,
-    collections::{HashMap, HashSet},
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env,
     fmt::{self, Display, Formatter},
     fs,
-    io::{self, IsTerminal, Write},
+    io::{self, IsTerminal, Read, Write},
+    iter::once,
+    mem,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
-    process::{self, Command},
+    process::{self, Child, Command, Stdio},
     str::FromStr,
-    time::{Duration, SystemTime},
+    sync::{
+      Arc, Mutex, OnceLock, PoisonError,
+      atomic::{self, AtomicU64},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
   },
   style::{BOLD, CYAN, DIM, GREEN, RED, Style, YELLOW},
   subcommand::Subcommand,
+  summary::Summary,
   system_time_ext::SystemTimeExt,
+  tally::Tally,
   task::Task,
+  trash::Trash,
+  usage::Usage,
   walkdir::WalkDir,
 };
 
 mod action;
 mod age;
+mod age_source;
 mod arguments;
 mod bytes;
 mod config;
 mod context;
+mod cutoff;
 mod detection;
+mod discovery;
+mod exclude;
+mod expression;
+mod failure;
+mod filesystem;
+mod format;
+mod git;
+mod guard;
+mod index;
+mod invocation;
+mod keep;
+mod local;
+mod manifest;
+mod output_mode;
 mod path_ext;
+mod plan;
+mod processes;
+mod project;
+mod reason;
+mod record;
 mod report;
 mod rule;
+mod selection;
+mod selector;
+mod skipped;
+mod sort;
 mod style;
 mod subcommand;
+mod summary;
 mod system_time_ext;
+mod tally;
 mod task;
+mod trash;
+mod usage;
 
 type Result<T = (), E = Error> = std::result::Result<T, E>;
 
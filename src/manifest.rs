@@ -0,0 +1,102 @@
+use super::*;
+
+/// A parsed TOML, JSON or YAML file, which detections can look keys up in.
+#[derive(Debug)]
+pub(crate) struct Manifest(serde_json::Value);
+
+impl Manifest {
+  /// Whether the dotted `key`, like `dependencies.next`, is present.
+  pub(crate) fn contains(&self, key: &str) -> bool {
+    key
+      .split('.')
+      .try_fold(&self.0, |value, segment| match value {
+        serde_json::Value::Array(items) => segment
+          .parse::<usize>()
+          .ok()
+          .and_then(|index| items.get(index)),
+        serde_json::Value::Object(map) => map.get(segment),
+        _ => None,
+      })
+      .is_some()
+  }
+
+  pub(crate) fn load(path: &Path) -> Result<Self> {
+    let contents = fs::read_to_string(path)?;
+
+    Ok(Self(match Self::syntax(path)? {
+      Syntax::Json => serde_json::from_str(&contents)?,
+      Syntax::Toml => toml::from_str(&contents)?,
+      Syntax::Yaml => serde_yaml_ng::from_str(&contents)?,
+    }))
+  }
+
+  /// Picks a syntax from the extension of `path`.
+  pub(crate) fn syntax(path: &Path) -> Result<Syntax> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+      Some("json") => Ok(Syntax::Json),
+      Some("toml") => Ok(Syntax::Toml),
+      Some("yaml" | "yml") => Ok(Syntax::Yaml),
+      _ => bail!(
+        "unsupported manifest `{}`, expected a .json, .toml, .yaml or .yml file",
+        path.display()
+      ),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Syntax {
+  Json,
+  Toml,
+  Yaml,
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  #[test]
+  fn contains_nested_keys() {
+    let tree = temptree! {
+      "package.json": r#"{"dependencies": {"next": "15.0.0"}, "files": ["a"]}"#,
+    };
+
+    let manifest = Manifest::load(&tree.path().join("package.json")).unwrap();
+
+    assert!(manifest.contains("dependencies"));
+    assert!(manifest.contains("dependencies.next"));
+    assert!(manifest.contains("files.0"));
+    assert!(!manifest.contains("dependencies.react"));
+    assert!(!manifest.contains("files.1"));
+  }
+
+  #[test]
+  fn loads_toml_and_yaml() {
+    let tree = temptree! {
+      "Cargo.toml": "[workspace]\nmembers = []\n",
+      "pubspec.yaml": "dependencies:\n  flutter:\n    sdk: flutter\n",
+    };
+
+    assert!(
+      Manifest::load(&tree.path().join("Cargo.toml"))
+        .unwrap()
+        .contains("workspace.members")
+    );
+
+    assert!(
+      Manifest::load(&tree.path().join("pubspec.yaml"))
+        .unwrap()
+        .contains("dependencies.flutter.sdk")
+    );
+  }
+
+  #[test]
+  fn rejects_unknown_formats() {
+    assert_eq!(
+      Manifest::syntax(Path::new("Makefile"))
+        .unwrap_err()
+        .to_string(),
+      "unsupported manifest `Makefile`, expected a .json, .toml, .yaml or .yml file",
+    );
+  }
+}
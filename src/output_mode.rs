@@ -0,0 +1,12 @@
+use super::*;
+
+/// Whether a command's output is captured or streamed.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum OutputMode {
+  #[default]
+  Capture,
+  Stream,
+}
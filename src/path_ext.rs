@@ -1,71 +1,53 @@
 use super::*;
 
 pub(crate) trait PathExt {
-  fn directories(&self, follow_symlinks: bool) -> Result<Vec<PathBuf>>;
-  fn size(&self, follow_symlinks: bool) -> Result<u64>;
+  fn size(&self, follow_symlinks: bool, usage: &Usage) -> Result<u64>;
 }
 
 impl PathExt for Path {
-  fn directories(&self, follow_symlinks: bool) -> Result<Vec<PathBuf>> {
-    let mut directories = Vec::new();
-
-    for entry in fs::read_dir(self)? {
-      let entry = entry?;
-
-      let path = entry.path();
-
-      let is_dir = if follow_symlinks {
-        path.is_dir()
-      } else {
-        entry
-          .file_type()
-          .map(|file_type| file_type.is_dir())
-          .unwrap_or(false)
-      };
-
-      if is_dir {
-        directories.push(path);
-      }
-    }
-
-    directories.sort_unstable();
-
-    Ok(directories)
-  }
-
-  fn size(&self, follow_symlinks: bool) -> Result<u64> {
+  fn size(&self, follow_symlinks: bool, usage: &Usage) -> Result<u64> {
     let metadata = if follow_symlinks {
       fs::metadata(self)?
     } else {
       fs::symlink_metadata(self)?
     };
 
-    if metadata.is_file() {
-      return Ok(metadata.len());
-    }
-
-    if !follow_symlinks && metadata.file_type().is_symlink() {
-      return Ok(metadata.len());
-    }
-
     if !metadata.is_dir() {
-      return Ok(0);
+      return Ok(usage.bytes(self, &metadata));
     }
 
-    let mut total = 0;
-
-    for entry in WalkDir::new(self).follow_links(follow_symlinks) {
-      let entry = entry?;
+    let children = fs::read_dir(self)?
+      .collect::<io::Result<Vec<_>>>()?
+      .into_par_iter()
+      .map(|entry| walk(self, &entry.path(), follow_symlinks, usage))
+      .sum::<Result<u64>>()?;
 
-      if entry.file_type().is_file() {
-        total += entry.metadata()?.len();
-      } else if !follow_symlinks && entry.file_type().is_symlink() {
-        total += fs::symlink_metadata(entry.path())?.len();
-      }
-    }
+    Ok(usage.bytes(self, &metadata) + children)
+  }
+}
 
-    Ok(total)
+/// Sums the size of everything at or below `path`, part of measuring `root`,
+/// on the current thread.
+fn walk(
+  root: &Path,
+  path: &Path,
+  follow_symlinks: bool,
+  usage: &Usage,
+) -> Result<u64> {
+  let mut total = 0;
+
+  for entry in WalkDir::new(path)
+    .follow_links(follow_symlinks)
+    .follow_root_links(follow_symlinks)
+  {
+    let entry = entry?;
+
+    usage.check(root, entry.path());
+
+    total += usage.bytes(root, &entry.metadata()?);
   }
+
+  Ok(total)
 }
 
 #[cfg(test)]
@@ -78,7 +60,14 @@ mod tests {
       "test.txt": "hello"
     };
 
-    assert_eq!(tree.path().join("test.txt").size(false).unwrap(), 5);
+    assert_eq!(
+      tree
+        .path()
+        .join("test.txt")
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      5
+    );
   }
 
   #[test]
@@ -87,12 +76,25 @@ mod tests {
       "empty.txt": ""
     };
 
-    assert_eq!(tree.path().join("empty.txt").size(false).unwrap(), 0);
+    assert_eq!(
+      tree
+        .path()
+        .join("empty.txt")
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      0
+    );
   }
 
   #[test]
   fn size_of_empty_directory() {
-    assert_eq!(temptree! {}.path().size(false).unwrap(), 0);
+    assert_eq!(
+      temptree! {}
+        .path()
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      0
+    );
   }
 
   #[test]
@@ -102,7 +104,13 @@ mod tests {
       "b.txt": "bbbbb"
     };
 
-    assert_eq!(tree.path().size(false).unwrap(), 8);
+    assert_eq!(
+      tree
+        .path()
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      8
+    );
   }
 
   #[test]
@@ -114,7 +122,13 @@ mod tests {
       }
     };
 
-    assert_eq!(tree.path().size(false).unwrap(), 10);
+    assert_eq!(
+      tree
+        .path()
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      10
+    );
   }
 
   #[test]
@@ -129,7 +143,13 @@ mod tests {
       }
     };
 
-    assert_eq!(tree.path().size(false).unwrap(), 6);
+    assert_eq!(
+      tree
+        .path()
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      6
+    );
   }
 
   #[test]
@@ -141,7 +161,14 @@ mod tests {
       }
     };
 
-    assert_eq!(tree.path().join("subdir").size(false).unwrap(), 6);
+    assert_eq!(
+      tree
+        .path()
+        .join("subdir")
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      6
+    );
   }
 
   #[test]
@@ -150,7 +177,14 @@ mod tests {
       "subdir": {}
     };
 
-    assert_eq!(tree.path().join("subdir").size(false).unwrap(), 0);
+    assert_eq!(
+      tree
+        .path()
+        .join("subdir")
+        .size(false, &Usage::new(true, &Exclude::default()))
+        .unwrap(),
+      0
+    );
   }
 
   #[test]
@@ -159,61 +193,30 @@ mod tests {
       temptree! {}
         .path()
         .join("does_not_exist")
-        .size(false)
+        .size(false, &Usage::new(true, &Exclude::default()))
         .is_err()
     );
   }
 
   #[test]
-  fn directories_returns_sorted_subdirectories() {
-    let tree = temptree! {
-      "file.txt": "content",
-      "zebra": {},
-      "alpha": {},
-      "middle": {}
-    };
-
-    let directories = tree.path().directories(false).unwrap();
-
-    assert_eq!(directories.len(), 3);
-
-    assert_eq!(directories[0], tree.path().join("alpha"));
-    assert_eq!(directories[1], tree.path().join("middle"));
-    assert_eq!(directories[2], tree.path().join("zebra"));
-  }
-
-  #[test]
-  fn directories_excludes_files() {
+  fn size_notes_excluded_paths() {
     let tree = temptree! {
-      "file1.txt": "content",
-      "file2.txt": "content",
-      "only_dir": {}
+      "vendor": {
+        "foo": {
+          "lib.rs": "",
+        },
+      },
     };
 
-    let directories = tree.path().directories(false).unwrap();
+    let exclude = Exclude::new(&["vendor/foo"]).unwrap();
 
-    assert_eq!(directories.len(), 1);
+    let usage = Usage::new(true, &exclude);
 
-    assert_eq!(*directories.first().unwrap(), tree.path().join("only_dir"));
-  }
-
-  #[test]
-  fn directories_empty_directory() {
-    let tree = temptree! {};
-
-    let directories = tree.path().directories(false).unwrap();
-
-    assert!(directories.is_empty());
-  }
+    for path in ["vendor", "vendor/foo"] {
+      tree.path().join(path).size(false, &usage).unwrap();
+    }
 
-  #[test]
-  fn directories_nonexistent_path_returns_error() {
-    assert!(
-      temptree! {}
-        .path()
-        .join("does_not_exist")
-        .directories(false)
-        .is_err()
-    );
+    assert!(usage.contains_excluded(&tree.path().join("vendor")));
+    assert!(!usage.contains_excluded(&tree.path().join("vendor/foo")));
   }
 }
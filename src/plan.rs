@@ -0,0 +1,170 @@
+use super::*;
+
+/// What `--free` decided to clean on one filesystem.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct Plan {
+  pub(crate) available: u64,
+  pub(crate) freed: u64,
+  pub(crate) needed: u64,
+  pub(crate) projects: usize,
+  pub(crate) root: PathBuf,
+}
+
+impl Display for Plan {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let style = Style::stdout();
+
+    write!(
+      f,
+      "{} {} available",
+      style.apply(CYAN, self.root.display()),
+      style.apply(GREEN, Bytes(self.available)),
+    )?;
+
+    if self.needed == 0 {
+      return writeln!(f, ", nothing to clean");
+    }
+
+    write!(
+      f,
+      ", {} more needed, cleaning {} {} ({})",
+      style.apply(BOLD, Bytes(self.needed)),
+      self.projects,
+      if self.projects == 1 {
+        "project"
+      } else {
+        "projects"
+      },
+      style.apply(GREEN, Bytes(self.freed)),
+    )?;
+
+    if self.freed < self.needed {
+      write!(
+        f,
+        " {}",
+        style.apply(YELLOW, "(not enough to reach the target)")
+      )?;
+    }
+
+    writeln!(f)
+  }
+}
+
+impl Plan {
+  /// Picks the stalest, largest projects until every filesystem has `target`,
+  /// skipping the rest.
+  pub(crate) fn make<'a>(
+    projects: Vec<Project<'a>>,
+    target: Bytes,
+  ) -> Result<(Vec<Project<'a>>, Vec<Plan>)> {
+    let now = SystemTime::now();
+
+    let mut groups = Vec::<(Filesystem, PathBuf, Vec<Project<'a>>)>::new();
+
+    let (cleanable, mut dropped) = projects
+      .into_iter()
+      .partition::<Vec<_>, _>(Project::cleanable);
+
+    for project in cleanable {
+      let filesystem = Filesystem::of(&project.context.root)?;
+
+      match groups
+        .iter_mut()
+        .find(|(existing, ..)| existing.device == filesystem.device)
+      {
+        Some((.., members)) => members.push(project),
+        None => groups.push((
+          filesystem,
+          project.context.index.root.clone(),
+          vec![project],
+        )),
+      }
+    }
+
+    let mut selected = Vec::new();
+
+    let mut plans = Vec::new();
+
+    for (filesystem, root, mut members) in groups {
+      let needed = target.0.saturating_sub(filesystem.available);
+
+      members.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+
+      let mut freed = 0;
+
+      let mut count = 0;
+
+      for mut project in members {
+        if freed >= needed {
+          for report in &mut project.reports {
+            report.skip = Some(Reason::Unneeded);
+          }
+
+          dropped.push(project);
+
+          continue;
+        }
+
+        freed += project.size();
+        count += 1;
+        selected.push(project);
+      }
+
+      plans.push(Plan {
+        available: filesystem.available,
+        freed,
+        needed,
+        projects: count,
+        root,
+      });
+    }
+
+    selected.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+
+    dropped.sort_by(|a, b| a.context.root.cmp(&b.context.root));
+
+    selected.extend(dropped);
+
+    Ok((selected, plans))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn plan(available: u64, needed: u64, freed: u64, projects: usize) -> String {
+    Plan {
+      available,
+      freed,
+      needed,
+      projects,
+      root: PathBuf::from("/code"),
+    }
+    .to_string()
+  }
+
+  #[test]
+  fn display_nothing_to_clean() {
+    assert_eq!(
+      plan(2048, 0, 0, 0),
+      "/code 2 KiB available, nothing to clean\n"
+    );
+  }
+
+  #[test]
+  fn display_cleaning() {
+    assert_eq!(
+      plan(1024, 1024, 2048, 1),
+      "/code 1 KiB available, 1 KiB more needed, cleaning 1 project (2 KiB)\n",
+    );
+  }
+
+  #[test]
+  fn display_shortfall() {
+    assert_eq!(
+      plan(0, 1024, 10, 2),
+      "/code 0 bytes available, 1 KiB more needed, cleaning 2 projects (10 bytes) (not enough to reach the target)\n",
+    );
+  }
+}
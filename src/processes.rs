@@ -0,0 +1,84 @@
+use super::*;
+
+/// Paths that running processes have as their working directory or hold open.
+#[derive(Debug)]
+pub(crate) struct Processes {
+  paths: Vec<PathBuf>,
+}
+
+impl Processes {
+  /// Whether any process is using a path at or below `root`.
+  pub(crate) fn active(&self, root: &Path) -> Result<bool> {
+    let root = fs::canonicalize(root)?;
+
+    Ok(self.paths.iter().any(|path| path.starts_with(&root)))
+  }
+
+  /// Scans `/proc` for paths used by processes other than our own.
+  #[cfg(target_os = "linux")]
+  pub(crate) fn scan() -> Result<Self> {
+    let mut paths = Vec::new();
+
+    for entry in fs::read_dir("/proc")? {
+      let entry = entry?;
+
+      let pid = entry
+        .file_name()
+        .to_str()
+        .and_then(|name| name.parse::<u32>().ok());
+
+      if pid.is_none_or(|pid| pid == process::id()) {
+        continue;
+      }
+
+      if let Ok(cwd) = fs::read_link(entry.path().join("cwd")) {
+        paths.push(cwd);
+      }
+
+      let Ok(descriptors) = fs::read_dir(entry.path().join("fd")) else {
+        continue;
+      };
+
+      paths.extend(
+        descriptors
+          .flatten()
+          .filter_map(|descriptor| fs::read_link(descriptor.path()).ok())
+          .filter(|target| target.is_absolute()),
+      );
+    }
+
+    Ok(Self { paths })
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  pub(crate) fn scan() -> Result<Self> {
+    bail!("checking for active processes is only supported on Linux")
+  }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  #[test]
+  fn finds_working_directories_of_other_processes() {
+    let tree = temptree! {
+      "busy": {},
+      "idle": {},
+    };
+
+    let mut child = Command::new("sleep")
+      .arg("10")
+      .current_dir(tree.path().join("busy"))
+      .spawn()
+      .unwrap();
+
+    let processes = Processes::scan().unwrap();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(processes.active(&tree.path().join("busy")).unwrap());
+    assert!(!processes.active(&tree.path().join("idle")).unwrap());
+  }
+}
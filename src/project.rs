@@ -0,0 +1,174 @@
+use super::*;
+
+#[derive(Debug)]
+pub(crate) struct Project<'a> {
+  pub(crate) context: Context<'a>,
+  pub(crate) reports: Vec<Report>,
+}
+
+impl<'a> Project<'a> {
+  /// Whether anything in the project is going to be cleaned.
+  pub(crate) fn cleanable(&self) -> bool {
+    self.reports.iter().all(|report| report.skip.is_none())
+      && self.reports.iter().any(|report| !report.tasks.is_empty())
+  }
+
+  /// Takes hard-linked files that an earlier project in `seen` already
+  /// counted off the project's removals, skipping any that are then too small.
+  pub(crate) fn credit(
+    &mut self,
+    rules: &[Box<dyn Rule>],
+    guard: &Guard,
+    usage: &Usage,
+    seen: &mut HashSet<(u64, u64)>,
+  ) {
+    for report in &mut self.reports {
+      let rule = rules.iter().find(|rule| rule.id() == report.rule_id);
+
+      let mut tasks = Vec::new();
+
+      for mut task in report.tasks.drain(..) {
+        if let Task::Remove { path, size } = &mut task {
+          let shared = usage.shared(&self.context.root.join(&path), seen);
+
+          if shared > 0 {
+            *size -= shared;
+
+            if rule.is_some_and(|rule| guard.small(rule.as_ref(), *size)) {
+              report.skipped.push(Skipped {
+                path: path.clone(),
+                reason: Reason::Small,
+              });
+              continue;
+            }
+          }
+        }
+
+        tasks.push(task);
+      }
+
+      report.tasks = tasks;
+    }
+  }
+
+  pub(crate) fn modified(&self) -> Option<SystemTime> {
+    self.reports.iter().map(|report| report.modified).max()
+  }
+
+  pub(crate) fn new(
+    context: Context<'a>,
+    rules: &[Box<dyn Rule>],
+    guard: &Guard,
+    usage: &Usage,
+  ) -> Result<Self> {
+    let mut reports = context
+      .rules(rules)
+      .filter(|rule| {
+        rule.detection().matches(&context) && guard.due(*rule, &context)
+      })
+      .map(|rule| {
+        let mut report = context.report(rule, usage)?;
+        Self::skip_removals(&context, rule, &mut report, guard, usage)?;
+        Ok(report)
+      })
+      .filter(|report| {
+        !report.as_ref().is_ok_and(|report| {
+          report.tasks.is_empty()
+            && report.skipped.is_empty()
+            && report.skip.is_none()
+            && report.missing_tools.is_empty()
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    let skip = if reports.is_empty() {
+      None
+    } else if let Some(error) = &context.local.invalid {
+      Some(Reason::Invalid(error.clone()))
+    } else if context.local.skip {
+      Some(Reason::OptedOut)
+    } else {
+      guard.check(&context, &reports)?
+    };
+
+    if let Some(reason) = skip {
+      for report in &mut reports {
+        report.skip = Some(reason.clone());
+      }
+    }
+
+    Ok(Self { context, reports })
+  }
+
+  /// The project's size, weighted by days since it was last modified.
+  pub(crate) fn score(&self, now: SystemTime) -> f64 {
+    #![allow(clippy::cast_precision_loss)]
+    let days = self
+      .modified()
+      .and_then(|modified| now.duration_since(modified).ok())
+      .map_or(0.0, |age| age.as_secs_f64() / 86_400.0);
+
+    self.size() as f64 * (1.0 + days)
+  }
+
+  /// The total size of the paths the project's reports would remove.
+  pub(crate) fn size(&self) -> u64 {
+    self
+      .reports
+      .iter()
+      .flat_map(|report| &report.tasks)
+      .map(|task| match task {
+        Task::Remove { size, .. } => *size,
+        Task::Command(_) => 0,
+      })
+      .sum()
+  }
+
+  /// Moves removals that must be left alone out of the report's tasks.
+  fn skip_removals(
+    context: &Context,
+    rule: &dyn Rule,
+    report: &mut Report,
+    guard: &Guard,
+    usage: &Usage,
+  ) -> Result {
+    let keep = Keep::new(rule.keep())?;
+
+    let mut tasks = Vec::new();
+
+    for task in report.tasks.drain(..) {
+      let Task::Remove { path, size } = &task else {
+        tasks.push(task);
+        continue;
+      };
+
+      let reason = if guard.excluded(&context.root.join(path)) {
+        Some(Reason::Excluded)
+      } else if guard.contains_excluded(&context.root.join(path), usage) {
+        Some(Reason::ContainsExcluded)
+      } else if keep.keeps(path) || context.local.keep.keeps(path) {
+        Some(Reason::Kept)
+      } else if !rule.allow_tracked()
+        && guard.tracked(&context.root.join(path))?
+      {
+        Some(Reason::Tracked)
+      } else if guard.small(rule, *size) {
+        Some(Reason::Small)
+      } else {
+        None
+      };
+
+      match reason {
+        Some(reason) => report.skipped.push(Skipped {
+          path: path.clone(),
+          reason,
+        }),
+        None => tasks.push(task),
+      }
+    }
+
+    report.tasks = tasks;
+
+    Ok(())
+  }
+}
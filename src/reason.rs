@@ -0,0 +1,39 @@
+use super::*;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Reason {
+  Active,
+  ContainsExcluded,
+  Dirty,
+  Excluded,
+  Invalid(String),
+  Kept,
+  OptedOut,
+  Small,
+  Tracked,
+  Unchecked(String),
+  Unneeded,
+}
+
+impl Display for Reason {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Active => write!(f, "in use by a running process"),
+      Self::ContainsExcluded => write!(f, "contains an excluded path"),
+      Self::Dirty => write!(f, "has uncommitted changes"),
+      Self::Excluded => write!(f, "excluded"),
+      Self::Invalid(error) => {
+        write!(f, "invalid {}: {error}", Local::FILENAME)
+      }
+      Self::Kept => write!(f, "matches a keep pattern"),
+      Self::OptedOut => write!(f, "opted out by {}", Local::FILENAME),
+      Self::Small => write!(f, "below the minimum size"),
+      Self::Tracked => write!(f, "tracked by git"),
+      Self::Unchecked(error) => {
+        write!(f, "couldn't check for uncommitted changes: {error}")
+      }
+      Self::Unneeded => write!(f, "not needed to reach the free space target"),
+    }
+  }
+}
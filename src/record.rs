@@ -0,0 +1,19 @@
+use super::*;
+
+/// The document printed by `--format json`.
+#[derive(Debug, Serialize)]
+pub(crate) struct Document<'a> {
+  #[serde(skip_serializing_if = "<[Plan]>::is_empty")]
+  pub(crate) plans: &'a [Plan],
+  pub(crate) projects: Vec<&'a Report>,
+  pub(crate) summary: &'a Summary,
+}
+
+/// A single line of `--format ndjson` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub(crate) enum Record<'a> {
+  Plan(&'a Plan),
+  Project(&'a Report),
+  Summary(&'a Summary),
+}
@@ -1,20 +1,33 @@
 use super::*;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub(crate) struct Report {
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub(crate) missing_tools: Vec<String>,
+  #[serde(serialize_with = "serialize_timestamp")]
   pub(crate) modified: SystemTime,
   pub(crate) root: PathBuf,
+  pub(crate) rule_id: String,
   pub(crate) rule_name: String,
+  pub(crate) skip: Option<Reason>,
+  pub(crate) skipped: Vec<Skipped>,
   pub(crate) tasks: Vec<Task>,
 }
 
+fn serialize_timestamp<S: Serializer>(
+  time: &SystemTime,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  serializer.serialize_u64(time.timestamp())
+}
+
 impl Display for Report {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     let style = Style::stdout();
 
     let age = self.modified.format();
 
-    writeln!(
+    write!(
       f,
       "{} {} project ({})",
       style.apply(CYAN, self.root.display()),
@@ -22,7 +35,41 @@ impl Display for Report {
       style.apply(DIM, age),
     )?;
 
-    let total_entries = self.tasks.len();
+    if !self.missing_tools.is_empty() {
+      write!(
+        f,
+        " {}",
+        style.apply(
+          YELLOW,
+          format_args!(
+            "({} not found, {})",
+            self
+              .missing_tools
+              .iter()
+              .map(|tool| format!("`{tool}`"))
+              .collect::<Vec<_>>()
+              .join(", "),
+            if self.tasks.is_empty() && self.skipped.is_empty() {
+              "nothing to remove instead"
+            } else {
+              "removing instead"
+            }
+          )
+        )
+      )?;
+    }
+
+    if let Some(reason) = &self.skip {
+      write!(
+        f,
+        " {}",
+        style.apply(YELLOW, format_args!("(skipped: {reason})"))
+      )?;
+    }
+
+    writeln!(f)?;
+
+    let total_entries = self.tasks.len() + self.skipped.len();
 
     for (index, task) in self.tasks.iter().enumerate() {
       let branch = if index + 1 == total_entries {
@@ -53,6 +100,22 @@ impl Display for Report {
       }
     }
 
+    for (index, skipped) in self.skipped.iter().enumerate() {
+      let branch = if self.tasks.len() + index + 1 == total_entries {
+        "└─"
+      } else {
+        "├─"
+      };
+
+      writeln!(
+        f,
+        "  {} {} {}",
+        style.apply(DIM, branch),
+        skipped.path.display(),
+        style.apply(YELLOW, format_args!("(skipped: {})", skipped.reason)),
+      )?;
+    }
+
     Ok(())
   }
 }
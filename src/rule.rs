@@ -8,7 +8,9 @@ macro_rules! define_rule {
       id: $id:literal,
       name: $rule_name:literal,
       detection: $detection:expr,
-      actions: [$($action:expr),* $(,)?] $(,)?
+      actions: [$($action:expr),* $(,)?],
+      $(keep: [$($keep:literal),* $(,)?],)?
+      tags: [$($tag:literal),* $(,)?] $(,)?
     }
   ) => {
     $(#[$doc])*
@@ -27,9 +29,19 @@ macro_rules! define_rule {
         $id
       }
 
+      $(
+        fn keep(&self) -> &[&str] {
+          &[$($keep),*]
+        }
+      )?
+
       fn name(&self) -> &str {
         $rule_name
       }
+
+      fn tags(&self) -> &[&str] {
+        &[$($tag),*]
+      }
     }
 
     inventory::submit!(&$name as &(dyn Rule + Sync));
@@ -64,14 +76,39 @@ pub(crate) trait Rule: Sync {
   /// A description of what the rule does.
   fn actions(&self) -> &[Action];
 
+  /// Whether the rule may remove paths tracked by git.
+  fn allow_tracked(&self) -> bool {
+    false
+  }
+
   /// Builds a detection used to evaluate a context.
   fn detection(&self) -> Detection;
 
   /// A unique identifier for the rule.
   fn id(&self) -> &str;
 
+  /// Globs for paths the rule must never remove.
+  fn keep(&self) -> &[&str] {
+    &[]
+  }
+
+  /// Paths smaller than this are left alone, overriding `--min-size`.
+  fn min_size(&self) -> Option<Bytes> {
+    None
+  }
+
   /// A human-readable name for the rule.
   fn name(&self) -> &str;
+
+  /// Projects newer than this are left alone, overriding `--older-than`.
+  fn older_than(&self) -> Option<Cutoff> {
+    None
+  }
+
+  /// Labels for selecting groups of rules, like `js` or `game-engine`.
+  fn tags(&self) -> &[&str] {
+    &[]
+  }
 }
 
 impl<T: Rule + ?Sized> Rule for &T {
@@ -79,6 +116,10 @@ impl<T: Rule + ?Sized> Rule for &T {
     (**self).actions()
   }
 
+  fn allow_tracked(&self) -> bool {
+    (**self).allow_tracked()
+  }
+
   fn detection(&self) -> Detection {
     (**self).detection()
   }
@@ -87,7 +128,23 @@ impl<T: Rule + ?Sized> Rule for &T {
     (**self).id()
   }
 
+  fn keep(&self) -> &[&str] {
+    (**self).keep()
+  }
+
+  fn min_size(&self) -> Option<Bytes> {
+    (**self).min_size()
+  }
+
   fn name(&self) -> &str {
     (**self).name()
   }
+
+  fn older_than(&self) -> Option<Cutoff> {
+    (**self).older_than()
+  }
+
+  fn tags(&self) -> &[&str] {
+    (**self).tags()
+  }
 }
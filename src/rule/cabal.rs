@@ -8,5 +8,8 @@ define_rule! {
     actions: [
       Action::Remove("dist-newstyle"),
     ],
+    tags: [
+      "haskell",
+    ],
   }
 }
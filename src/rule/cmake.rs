@@ -10,5 +10,9 @@ define_rule! {
       Action::Remove("cmake-build-debug"),
       Action::Remove("cmake-build-release"),
     ],
+    tags: [
+      "c",
+      "cpp",
+    ],
   }
 }
@@ -18,5 +18,8 @@ define_rule! {
       Action::Remove("bin"),
       Action::Remove("obj"),
     ],
+    tags: [
+      "dotnet",
+    ],
   }
 }
@@ -11,5 +11,8 @@ define_rule! {
       Action::Remove(".elixir_ls"),
       Action::Remove(".lexical"),
     ],
+    tags: [
+      "elixir",
+    ],
   }
 }
@@ -12,5 +12,8 @@ define_rule! {
       Action::Remove("build"),
       Action::Remove(".gradle"),
     ],
+    tags: [
+      "jvm",
+    ],
   }
 }
@@ -8,5 +8,8 @@ define_rule! {
     actions: [
       Action::Remove("**/.ipynb_checkpoints"),
     ],
+    tags: [
+      "python",
+    ],
   }
 }
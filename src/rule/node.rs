@@ -9,5 +9,8 @@ define_rule! {
       Action::Remove("**/node_modules"),
       Action::Remove(".angular"),
     ],
+    tags: [
+      "js",
+    ],
   }
 }
@@ -11,5 +11,8 @@ define_rule! {
       Action::Remove("linux/flutter/ephemeral"),
       Action::Remove("windows/flutter/ephemeral"),
     ],
+    tags: [
+      "dart",
+    ],
   }
 }
@@ -15,5 +15,8 @@ define_rule! {
       Action::Remove("__pycache__"),
       Action::Remove("__pypackages__"),
     ],
+    tags: [
+      "python",
+    ],
   }
 }
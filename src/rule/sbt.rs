@@ -9,5 +9,9 @@ define_rule! {
       Action::Remove("target"),
       Action::Remove("project/target"),
     ],
+    tags: [
+      "jvm",
+      "scala",
+    ],
   }
 }
@@ -9,5 +9,8 @@ define_rule! {
       Action::Remove(".build"),
       Action::Remove(".swiftpm"),
     ],
+    tags: [
+      "swift",
+    ],
   }
 }
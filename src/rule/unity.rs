@@ -14,5 +14,8 @@ define_rule! {
       Action::Remove("Build"),
       Action::Remove("Builds"),
     ],
+    tags: [
+      "game-engine",
+    ],
   }
 }
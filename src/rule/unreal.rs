@@ -12,5 +12,8 @@ define_rule! {
       Action::Remove("DerivedDataCache"),
       Action::Remove("Intermediate"),
     ],
+    tags: [
+      "game-engine",
+    ],
   }
 }
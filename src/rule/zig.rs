@@ -10,5 +10,8 @@ define_rule! {
       Action::Remove(".zig-cache"),
       Action::Remove("zig-out"),
     ],
+    tags: [
+      "zig",
+    ],
   }
 }
@@ -0,0 +1,114 @@
+use super::*;
+
+#[derive(Args, Debug, Default)]
+pub(crate) struct Selection {
+  #[arg(
+    long = "rule",
+    global = true,
+    value_name = "ID",
+    help = "Only run the rule with this id, may be given multiple times"
+  )]
+  rules: Vec<String>,
+  #[arg(
+    long = "skip-rule",
+    global = true,
+    value_name = "ID",
+    help = "Don't run the rule with this id, may be given multiple times"
+  )]
+  skip_rules: Vec<String>,
+  #[arg(
+    long = "skip-tag",
+    global = true,
+    value_name = "TAG",
+    help = "Don't run rules with this tag, may be given multiple times"
+  )]
+  skip_tags: Vec<String>,
+  #[arg(
+    long = "tag",
+    global = true,
+    value_name = "TAG",
+    help = "Only run rules with this tag, may be given multiple times"
+  )]
+  tags: Vec<String>,
+}
+
+impl Selection {
+  /// Checks that every id and tag given names at least one rule.
+  pub(crate) fn check(&self, rules: &[Box<dyn Rule>]) -> Result {
+    for id in self.rules.iter().chain(&self.skip_rules) {
+      ensure!(
+        rules.iter().any(|rule| rule.id() == id),
+        "no enabled rule with id `{id}`"
+      );
+    }
+
+    for tag in self.tags.iter().chain(&self.skip_tags) {
+      ensure!(
+        rules.iter().any(|rule| rule.tags().contains(&tag.as_str())),
+        "no enabled rule with tag `{tag}`"
+      );
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn selects<T: AsRef<str>>(&self, id: &str, tags: &[T]) -> bool {
+    let tagged = |wanted: &[String]| {
+      tags
+        .iter()
+        .any(|tag| wanted.iter().any(|wanted| wanted == tag.as_ref()))
+    };
+
+    let selected = (self.rules.is_empty() && self.tags.is_empty())
+      || self.rules.iter().any(|rule| rule == id)
+      || tagged(&self.tags);
+
+    selected
+      && !self.skip_rules.iter().any(|rule| rule == id)
+      && !tagged(&self.skip_tags)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(arguments: &[&str]) -> Selection {
+    #[derive(Parser)]
+    struct Command {
+      #[command(flatten)]
+      selection: Selection,
+    }
+
+    Command::try_parse_from(once("swab").chain(arguments.iter().copied()))
+      .unwrap()
+      .selection
+  }
+
+  #[test]
+  fn everything_is_selected_by_default() {
+    assert!(parse(&[]).selects("cargo", &["rust"]));
+  }
+
+  #[test]
+  fn rules_and_tags_select_their_union() {
+    let selection = parse(&["--rule", "cargo", "--tag", "js"]);
+
+    assert!(selection.selects("cargo", &["rust"]));
+    assert!(selection.selects("node", &["js"]));
+    assert!(!selection.selects("python", &["python"]));
+  }
+
+  #[test]
+  fn skips_take_precedence() {
+    let selection = parse(&["--tag", "js", "--skip-rule", "next"]);
+
+    assert!(selection.selects("node", &["js"]));
+    assert!(!selection.selects("next", &["js"]));
+
+    let selection = parse(&["--skip-tag", "game-engine"]);
+
+    assert!(selection.selects("cargo", &["rust"]));
+    assert!(!selection.selects("unity", &["game-engine"]));
+  }
+}
@@ -0,0 +1,725 @@
+use {
+  super::*,
+  crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{
+      self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+    },
+  },
+};
+
+const HELP: &str = "↑/↓ move  space toggle  a all  n none  s sort  r rule  \
+                    enter clean  q cancel";
+
+/// A project along with which of its reports' tasks are selected.
+#[derive(Debug)]
+struct Entry<'a> {
+  project: Project<'a>,
+  selected: Vec<Vec<bool>>,
+}
+
+/// What a keypress means for the selection as a whole.
+#[derive(Debug, Eq, PartialEq)]
+enum Outcome {
+  Cancel,
+  Confirm,
+  Continue,
+}
+
+/// A line of the list: one of a project's reports, or one of its tasks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Row {
+  Report {
+    entry: usize,
+    report: usize,
+  },
+  Task {
+    entry: usize,
+    report: usize,
+    task: usize,
+  },
+}
+
+/// Raw mode and the alternate screen, restored when dropped, even if the
+/// selector errors or panics.
+#[derive(Debug)]
+struct Screen;
+
+/// A full-screen list for picking what `--select` cleans.
+#[derive(Debug)]
+pub(crate) struct Selector<'a> {
+  cursor: usize,
+  entries: Vec<Entry<'a>>,
+  filter: Option<String>,
+  offset: usize,
+  sort: Sort,
+}
+
+impl<'a> Selector<'a> {
+  fn draw(&mut self, out: &mut impl Write, width: u16, height: u16) -> Result {
+    let rows = self.rows();
+
+    let visible = usize::from(height.saturating_sub(2)).max(1);
+
+    if self.cursor < self.offset {
+      self.offset = self.cursor;
+    } else if self.cursor >= self.offset + visible {
+      self.offset = self.cursor + 1 - visible;
+    }
+
+    let width = usize::from(width);
+
+    let fit = |text: String| text.chars().take(width).collect::<String>();
+
+    queue!(
+      out,
+      Clear(ClearType::All),
+      MoveTo(0, 0),
+      SetAttribute(Attribute::Bold),
+      Print(fit(self.header())),
+      SetAttribute(Attribute::Reset),
+    )?;
+
+    for (line, (index, row)) in rows
+      .iter()
+      .enumerate()
+      .skip(self.offset)
+      .take(visible)
+      .enumerate()
+    {
+      let y = u16::try_from(line + 1).unwrap_or(u16::MAX);
+
+      queue!(out, MoveTo(0, y))?;
+
+      if index == self.cursor {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+      }
+
+      queue!(
+        out,
+        Print(fit(self.line(*row))),
+        SetAttribute(Attribute::Reset)
+      )?;
+    }
+
+    queue!(
+      out,
+      MoveTo(0, height.saturating_sub(1)),
+      SetAttribute(Attribute::Dim),
+      Print(fit(HELP.into())),
+      SetAttribute(Attribute::Reset),
+    )?;
+
+    out.flush()?;
+
+    Ok(())
+  }
+
+  fn handle(&mut self, key: KeyEvent, page: usize) -> Outcome {
+    let last = self.rows().len().saturating_sub(1);
+
+    match key.code {
+      KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        return Outcome::Cancel;
+      }
+      KeyCode::Char('q') | KeyCode::Esc => return Outcome::Cancel,
+      KeyCode::Enter => return Outcome::Confirm,
+      KeyCode::Up | KeyCode::Char('k') => {
+        self.cursor = self.cursor.saturating_sub(1);
+      }
+      KeyCode::Down | KeyCode::Char('j') => {
+        self.cursor = (self.cursor + 1).min(last);
+      }
+      KeyCode::PageUp => self.cursor = self.cursor.saturating_sub(page),
+      KeyCode::PageDown => self.cursor = (self.cursor + page).min(last),
+      KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+      KeyCode::End | KeyCode::Char('G') => self.cursor = last,
+      KeyCode::Char(' ') => {
+        if let Some(row) = self.rows().get(self.cursor).copied() {
+          let selected = self.state(row) != Some(true);
+          self.set(row, selected);
+        }
+      }
+      KeyCode::Char('a') => self.set_all(true),
+      KeyCode::Char('n') => self.set_all(false),
+      KeyCode::Char('s') => {
+        self.sort = match self.sort {
+          Sort::Size => Sort::Age,
+          Sort::Age => Sort::Path,
+          Sort::Path => Sort::Size,
+        };
+        self.sort_entries();
+        self.cursor = 0;
+      }
+      KeyCode::Char('r') => {
+        let rules = self.rules();
+
+        self.filter = match &self.filter {
+          None => rules.first().cloned(),
+          Some(filter) => rules
+            .iter()
+            .skip_while(|rule| *rule != filter)
+            .nth(1)
+            .cloned(),
+        };
+
+        self.cursor = 0;
+      }
+      _ => {}
+    }
+
+    Outcome::Continue
+  }
+
+  fn header(&self) -> String {
+    let (count, bytes) = self
+      .entries
+      .iter()
+      .flat_map(|entry| {
+        entry
+          .project
+          .reports
+          .iter()
+          .zip(&entry.selected)
+          .flat_map(|(report, selected)| report.tasks.iter().zip(selected))
+      })
+      .filter(|(_, selected)| **selected)
+      .fold((0, 0), |(count, bytes), (task, _)| match task {
+        Task::Command(_) => (count + 1, bytes),
+        Task::Remove { size, .. } => (count + 1, bytes + size),
+      });
+
+    let sort = self
+      .sort
+      .to_possible_value()
+      .map(|value| value.get_name().to_owned())
+      .unwrap_or_default();
+
+    format!(
+      "{count} selected ({}), sorted by {sort}, showing {}",
+      Bytes(bytes),
+      match &self.filter {
+        Some(rule) => format!("rule {rule}"),
+        None => "all rules".into(),
+      }
+    )
+  }
+
+  fn interact(&mut self, out: &mut impl Write) -> Result<Outcome> {
+    loop {
+      let (width, height) = match terminal::size()? {
+        (0, _) | (_, 0) => (80, 24),
+        size => size,
+      };
+
+      self.draw(out, width, height)?;
+
+      if let Event::Key(key) = event::read()?
+        && key.kind == KeyEventKind::Press
+      {
+        let page = usize::from(height.saturating_sub(2)).max(1);
+
+        match self.handle(key, page) {
+          Outcome::Continue => {}
+          outcome => return Ok(outcome),
+        }
+      }
+    }
+  }
+
+  /// Keeps only the selected tasks.
+  fn into_projects(self) -> Vec<Project<'a>> {
+    self
+      .entries
+      .into_iter()
+      .filter_map(|entry| {
+        let mut project = entry.project;
+
+        project.reports = project
+          .reports
+          .into_iter()
+          .zip(entry.selected)
+          .filter_map(|(mut report, selected)| {
+            let mut selected = selected.into_iter();
+            report.tasks.retain(|_| selected.next().unwrap_or_default());
+            (!report.tasks.is_empty()).then_some(report)
+          })
+          .collect();
+
+        (!project.reports.is_empty()).then_some(project)
+      })
+      .collect()
+  }
+
+  fn line(&self, row: Row) -> String {
+    let checkbox = |state| match state {
+      Some(true) => "[x]",
+      Some(false) => "[ ]",
+      None => "[-]",
+    };
+
+    match row {
+      Row::Report { entry, report } => {
+        let report = &self.entries[entry].project.reports[report];
+
+        let size = report
+          .tasks
+          .iter()
+          .map(|task| match task {
+            Task::Command(_) => 0,
+            Task::Remove { size, .. } => *size,
+          })
+          .sum::<u64>();
+
+        format!(
+          "{} {} {} ({}, {})",
+          checkbox(self.state(row)),
+          report.root.display(),
+          report.rule_name,
+          Bytes(size),
+          report.modified.format(),
+        )
+      }
+      Row::Task {
+        entry,
+        report,
+        task,
+      } => {
+        let text =
+          match &self.entries[entry].project.reports[report].tasks[task] {
+            Task::Command(command) => format!("run {command}"),
+            Task::Remove { path, size } => {
+              format!("{} ({})", path.display(), Bytes(*size))
+            }
+          };
+
+        format!("    {} {text}", checkbox(self.state(row)))
+      }
+    }
+  }
+
+  fn new(projects: Vec<Project<'a>>, sort: Sort) -> Self {
+    let entries = projects
+      .into_iter()
+      .map(|project| Entry {
+        selected: project
+          .reports
+          .iter()
+          .map(|report| vec![true; report.tasks.len()])
+          .collect(),
+        project,
+      })
+      .collect();
+
+    let mut selector = Self {
+      cursor: 0,
+      entries,
+      filter: None,
+      offset: 0,
+      sort,
+    };
+
+    selector.sort_entries();
+
+    selector
+  }
+
+  fn rows(&self) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    for (entry, Entry { project, .. }) in self.entries.iter().enumerate() {
+      for (report, data) in project.reports.iter().enumerate() {
+        if self
+          .filter
+          .as_ref()
+          .is_some_and(|filter| *filter != data.rule_id)
+        {
+          continue;
+        }
+
+        rows.push(Row::Report { entry, report });
+
+        rows.extend((0..data.tasks.len()).map(|task| Row::Task {
+          entry,
+          report,
+          task,
+        }));
+      }
+    }
+
+    rows
+  }
+
+  /// The ids of every rule with a report in the list.
+  fn rules(&self) -> Vec<String> {
+    self
+      .entries
+      .iter()
+      .flat_map(|entry| &entry.project.reports)
+      .map(|report| report.rule_id.clone())
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .collect()
+  }
+
+  /// Returns the selected projects, or `None` if the user cancels.
+  pub(crate) fn run(
+    projects: Vec<Project<'a>>,
+    sort: Sort,
+  ) -> Result<Option<Vec<Project<'a>>>> {
+    let mut selector = Self::new(projects, sort);
+
+    if selector.entries.is_empty() {
+      return Ok(Some(Vec::new()));
+    }
+
+    let screen = Screen::enter()?;
+
+    let outcome = selector.interact(&mut io::stdout());
+
+    drop(screen);
+
+    Ok((outcome? == Outcome::Confirm).then(|| selector.into_projects()))
+  }
+
+  /// Selects or deselects a task, or every task of a report.
+  fn set(&mut self, row: Row, value: bool) {
+    match row {
+      Row::Report { entry, report } => {
+        self.entries[entry].selected[report].fill(value);
+      }
+      Row::Task {
+        entry,
+        report,
+        task,
+      } => self.entries[entry].selected[report][task] = value,
+    }
+  }
+
+  fn set_all(&mut self, value: bool) {
+    for row in self.rows() {
+      self.set(row, value);
+    }
+  }
+
+  fn sort_entries(&mut self) {
+    self
+      .sort
+      .sort_by(&mut self.entries, |entry: &Entry| &entry.project);
+  }
+
+  /// Whether a row is selected, or `None` if only partly.
+  fn state(&self, row: Row) -> Option<bool> {
+    match row {
+      Row::Report { entry, report } => {
+        let selected = &self.entries[entry].selected[report];
+
+        if selected.iter().all(|selected| *selected) {
+          Some(true)
+        } else if selected.iter().any(|selected| *selected) {
+          None
+        } else {
+          Some(false)
+        }
+      }
+      Row::Task {
+        entry,
+        report,
+        task,
+      } => Some(self.entries[entry].selected[report][task]),
+    }
+  }
+}
+
+impl Screen {
+  fn enter() -> Result<Self> {
+    terminal::enable_raw_mode()?;
+
+    let screen = Self;
+
+    execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+
+    Ok(screen)
+  }
+}
+
+impl Drop for Screen {
+  fn drop(&mut self) {
+    execute!(io::stdout(), Show, LeaveAlternateScreen).ok();
+    terminal::disable_raw_mode().ok();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+  }
+
+  fn index(tree: &tempfile::TempDir) -> Index {
+    Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap()
+  }
+
+  fn project<'a>(
+    index: &'a Index,
+    name: &str,
+    days: u64,
+    reports: &[(&str, &[(&str, u64)])],
+  ) -> Project<'a> {
+    let context = index.context(Path::new(name)).unwrap();
+
+    let modified = SystemTime::now() - Duration::from_hours(24 * days);
+
+    Project {
+      reports: reports
+        .iter()
+        .map(|(rule, tasks)| Report {
+          missing_tools: Vec::new(),
+          modified,
+          root: context.root.clone(),
+          rule_id: (*rule).into(),
+          rule_name: (*rule).into(),
+          skip: None,
+          skipped: Vec::new(),
+          tasks: tasks
+            .iter()
+            .map(|(path, size)| Task::Remove {
+              path: PathBuf::from(path),
+              size: *size,
+            })
+            .collect(),
+        })
+        .collect(),
+      context,
+    }
+  }
+
+  fn paths(projects: &[Project]) -> Vec<String> {
+    projects
+      .iter()
+      .flat_map(|project| &project.reports)
+      .flat_map(|report| {
+        report.tasks.iter().map(|task| match task {
+          Task::Command(command) => format!("{}: {command}", report.rule_id),
+          Task::Remove { path, .. } => {
+            format!("{}: {}", report.rule_id, path.display())
+          }
+        })
+      })
+      .collect()
+  }
+
+  #[test]
+  fn everything_starts_selected_and_sorted_by_size() {
+    let tree = temptree! {
+      "small": {},
+      "large": {},
+    };
+
+    let index = index(&tree);
+
+    let selector = Selector::new(
+      vec![
+        project(&index, "small", 1, &[("node", &[("node_modules", 10)])]),
+        project(&index, "large", 2, &[("cargo", &[("target", 100)])]),
+      ],
+      Sort::Size,
+    );
+
+    assert_eq!(
+      selector.header(),
+      "2 selected (110 bytes), sorted by size, showing all rules"
+    );
+
+    assert!(selector.line(selector.rows()[0]).starts_with("[x] "));
+    assert!(
+      selector
+        .line(selector.rows()[0])
+        .contains("cargo (100 bytes, 2 days ago)")
+    );
+    assert_eq!(
+      selector.line(selector.rows()[1]),
+      "    [x] target (100 bytes)"
+    );
+
+    assert_eq!(
+      paths(&selector.into_projects()),
+      ["cargo: target", "node: node_modules"],
+    );
+  }
+
+  #[test]
+  fn toggling_tasks_and_reports() {
+    let tree = temptree! {
+      "app": {},
+    };
+
+    let index = index(&tree);
+
+    let mut selector = Selector::new(
+      vec![project(
+        &index,
+        "app",
+        1,
+        &[
+          ("node", &[("node_modules", 10), ("dist", 5)]),
+          ("python", &[("__pycache__", 1)]),
+        ],
+      )],
+      Sort::Size,
+    );
+
+    selector.handle(key(KeyCode::Down), 10);
+    selector.handle(key(KeyCode::Char(' ')), 10);
+
+    assert_eq!(selector.state(selector.rows()[0]), None);
+    assert!(selector.line(selector.rows()[0]).starts_with("[-] "));
+
+    selector.handle(key(KeyCode::Up), 10);
+    selector.handle(key(KeyCode::Char(' ')), 10);
+
+    assert_eq!(selector.state(selector.rows()[0]), Some(true));
+
+    selector.handle(key(KeyCode::Char(' ')), 10);
+
+    assert_eq!(selector.state(selector.rows()[0]), Some(false));
+
+    assert_eq!(paths(&selector.into_projects()), ["python: __pycache__"]);
+  }
+
+  #[test]
+  fn bulk_selection_follows_the_rule_filter() {
+    let tree = temptree! {
+      "a": {},
+      "b": {},
+    };
+
+    let index = index(&tree);
+
+    let mut selector = Selector::new(
+      vec![
+        project(&index, "a", 1, &[("cargo", &[("target", 100)])]),
+        project(&index, "b", 1, &[("node", &[("node_modules", 10)])]),
+      ],
+      Sort::Size,
+    );
+
+    selector.handle(key(KeyCode::Char('r')), 10);
+
+    assert_eq!(selector.filter.as_deref(), Some("cargo"));
+    assert_eq!(selector.rows().len(), 2);
+
+    selector.handle(key(KeyCode::Char('n')), 10);
+    selector.handle(key(KeyCode::Char('r')), 10);
+
+    assert_eq!(selector.filter.as_deref(), Some("node"));
+
+    selector.handle(key(KeyCode::Char('r')), 10);
+
+    assert_eq!(selector.filter, None);
+    assert_eq!(selector.rows().len(), 4);
+
+    assert_eq!(paths(&selector.into_projects()), ["node: node_modules"]);
+  }
+
+  #[test]
+  fn sorting_by_age() {
+    let tree = temptree! {
+      "new": {},
+      "old": {},
+    };
+
+    let index = index(&tree);
+
+    let mut selector = Selector::new(
+      vec![
+        project(&index, "new", 1, &[("cargo", &[("target", 100)])]),
+        project(&index, "old", 30, &[("node", &[("node_modules", 10)])]),
+      ],
+      Sort::Size,
+    );
+
+    selector.handle(key(KeyCode::Char('s')), 10);
+
+    assert_eq!(selector.sort, Sort::Age);
+
+    assert_eq!(
+      paths(&selector.into_projects()),
+      ["node: node_modules", "cargo: target"],
+    );
+  }
+
+  #[test]
+  fn confirming_and_cancelling() {
+    let tree = temptree! {
+      "app": {},
+    };
+
+    let index = index(&tree);
+
+    let mut selector = Selector::new(
+      vec![project(&index, "app", 1, &[("cargo", &[("target", 100)])])],
+      Sort::Size,
+    );
+
+    assert_eq!(selector.handle(key(KeyCode::Enter), 10), Outcome::Confirm);
+    assert_eq!(
+      selector.handle(key(KeyCode::Char('q')), 10),
+      Outcome::Cancel
+    );
+    assert_eq!(selector.handle(key(KeyCode::Esc), 10), Outcome::Cancel);
+    assert_eq!(
+      selector
+        .handle(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), 10),
+      Outcome::Cancel
+    );
+    assert_eq!(selector.handle(key(KeyCode::Down), 10), Outcome::Continue);
+  }
+
+  #[test]
+  fn draw_keeps_the_cursor_in_view() {
+    let tree = temptree! {
+      "app": {},
+    };
+
+    let index = index(&tree);
+
+    let mut selector = Selector::new(
+      vec![project(
+        &index,
+        "app",
+        1,
+        &[(
+          "cargo",
+          &[("a", 10), ("b", 10), ("c", 10), ("d", 10), ("e", 10)],
+        )],
+      )],
+      Sort::Size,
+    );
+
+    selector.handle(key(KeyCode::End), 10);
+
+    let mut out = Vec::new();
+
+    selector.draw(&mut out, 40, 5).unwrap();
+
+    assert_eq!(selector.offset, 3);
+
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.contains("[x] e (10 bytes)"));
+    assert!(!out.contains("[x] a (10 bytes)"));
+  }
+}
@@ -0,0 +1,8 @@
+use super::*;
+
+/// A path a rule would have removed, and why it was left alone.
+#[derive(Debug, Serialize)]
+pub(crate) struct Skipped {
+  pub(crate) path: PathBuf,
+  pub(crate) reason: Reason,
+}
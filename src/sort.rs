@@ -0,0 +1,34 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub(crate) enum Sort {
+  Age,
+  Path,
+  Size,
+}
+
+impl Sort {
+  /// Orders `projects` oldest first, by path, or largest first.
+  pub(crate) fn sort(self, projects: &mut [Project]) {
+    self.sort_by(projects, |project| project);
+  }
+
+  /// Orders `items` the same way, by the project each one holds.
+  pub(crate) fn sort_by<'a, T>(
+    self,
+    items: &mut [T],
+    project: impl Fn(&T) -> &Project<'a>,
+  ) {
+    match self {
+      Self::Age => items.sort_by_cached_key(|item| project(item).modified()),
+      Self::Path => {
+        items.sort_by(|a, b| {
+          project(a).context.root.cmp(&project(b).context.root)
+        });
+      }
+      Self::Size => {
+        items.sort_by_cached_key(|item| Reverse(project(item).size()));
+      }
+    }
+  }
+}
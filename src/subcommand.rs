@@ -1,17 +1,27 @@
-use super::*;
+use {super::*, config_command::ConfigCommand, restore::Restore};
 
+mod config_command;
+mod restore;
 mod rules;
 
 #[derive(Debug, Parser)]
 pub(crate) enum Subcommand {
+  #[command(subcommand, about = "Manage the config file")]
+  Config(ConfigCommand),
+  #[command(about = "Restore items from the trash")]
+  Restore(Restore),
   #[command(about = "List all available rules")]
   Rules,
 }
 
 impl Subcommand {
-  pub(crate) fn run(self) -> Result {
+  pub(crate) fn run(self, arguments: &Arguments) -> Result {
     match self {
-      Self::Rules => rules::run(),
+      Self::Config(command) => command.run(arguments),
+      Self::Restore(restore) => restore.run(arguments.load_config()?),
+      Self::Rules => {
+        rules::run(arguments.load_config()?, arguments.selection())
+      }
     }
   }
 }
@@ -0,0 +1,371 @@
+use {super::*, toml::Spanned};
+
+#[derive(Debug, Parser)]
+pub(crate) enum ConfigCommand {
+  #[command(about = "Write a starter config file listing the built-in rules")]
+  Init {
+    #[arg(long, help = "Overwrite an existing config file")]
+    force: bool,
+  },
+  #[command(about = "Print the location of the config file")]
+  Path,
+  #[command(about = "Check the config file for errors")]
+  Validate,
+}
+
+/// A config file, with the locations of the settings that can be invalid.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SpannedConfig {
+  #[serde(alias = "default")]
+  default_rules: SpannedDefaultRules,
+  exclude: Vec<Spanned<String>>,
+  rules: Vec<Spanned<SpannedRule>>,
+  trash: SpannedTrash,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SpannedDefaultRules {
+  disabled: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpannedRule {
+  #[serde(default)]
+  actions: Vec<Spanned<ConfigAction>>,
+  detection: Spanned<ConfigDetection>,
+  id: Spanned<String>,
+  #[serde(default)]
+  keep: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SpannedTrash {
+  directory: Option<Spanned<PathBuf>>,
+}
+
+impl ConfigCommand {
+  fn init(path: &Path, force: bool) -> Result {
+    ensure!(
+      force || !path.exists(),
+      "config file `{}` already exists, pass `--force` to overwrite it",
+      path.display()
+    );
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, Self::starter())?;
+
+    println!("Wrote starter config to {}", path.display());
+
+    Ok(())
+  }
+
+  /// Converts a byte offset into `source` to a one-based line and column.
+  fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+
+    let line = before.matches('\n').count() + 1;
+
+    let column = before
+      .rfind('\n')
+      .map_or(before, |newline| &before[newline + 1..])
+      .chars()
+      .count()
+      + 1;
+
+    (line, column)
+  }
+
+  pub(crate) fn run(self, arguments: &Arguments) -> Result {
+    let path = arguments.config_path()?;
+
+    match self {
+      Self::Init { force } => Self::init(&path, force),
+      Self::Path => {
+        println!("{}", path.display());
+        Ok(())
+      }
+      Self::Validate => {
+        let source = fs::read_to_string(&path).map_err(|error| {
+          anyhow!("failed to read config file `{}`: {error}", path.display())
+        })?;
+
+        let errors = Self::validate(&source);
+
+        if errors.is_empty() {
+          println!("{} is valid", path.display());
+          return Ok(());
+        }
+
+        for (offset, message) in &errors {
+          let (line, column) = Self::position(&source, *offset);
+          eprintln!("{}:{line}:{column}: {message}", path.display());
+        }
+
+        bail!("config file `{}` is invalid", path.display())
+      }
+    }
+  }
+
+  fn starter() -> String {
+    let mut rules = Config::default_rules().collect::<Vec<_>>();
+
+    rules.sort_by(|a, b| a.id().cmp(b.id()));
+
+    let width = rules.iter().map(|rule| rule.id().len()).max().unwrap_or(0);
+
+    let mut starter = String::from(
+      "# swab config, see https://github.com/terror/swab for details.\n\
+       #\n\
+       # Built-in rules:\n\
+       #\n",
+    );
+
+    starter.extend(
+      rules
+        .iter()
+        .map(|rule| format!("#   {:width$}  {}\n", rule.id(), rule.name())),
+    );
+
+    starter.push_str(
+      "\n\
+       # Globs for paths swab should never touch.\n\
+       # exclude = [\"~/code/critical\"]\n\
+       \n\
+       [default]\n\
+       # Ids of built-in rules that should never run.\n\
+       disabled = []\n\
+       \n\
+       # Custom rules. A rule with the same id as a built-in rule replaces it.\n\
+       #\n\
+       # [[rules]]\n\
+       # id = \"make\"\n\
+       # name = \"Make\"\n\
+       # detection = \"Makefile\"\n\
+       # actions = [{ remove = \"build\" }]\n\
+       \n\
+       # [trash]\n\
+       # expire_after = \"30d\"\n",
+    );
+
+    starter
+  }
+
+  /// Returns every problem in `source` along with its byte offset.
+  fn validate(source: &str) -> Vec<(usize, String)> {
+    if let Err(error) = toml::from_str::<Config>(source) {
+      return vec![(
+        error.span().map_or(0, |span| span.start),
+        error.message().to_owned(),
+      )];
+    }
+
+    let config = match toml::from_str::<SpannedConfig>(source) {
+      Ok(config) => config,
+      Err(error) => {
+        return vec![(
+          error.span().map_or(0, |span| span.start),
+          error.message().to_owned(),
+        )];
+      }
+    };
+
+    let mut errors = Vec::new();
+
+    let mut check = |offset: usize, result: Result| {
+      if let Err(error) = result {
+        errors.push((offset, error.to_string()));
+      }
+    };
+
+    let builtin = Config::default_rules()
+      .map(Rule::id)
+      .collect::<HashSet<_>>();
+
+    for id in &config.default_rules.disabled {
+      check(
+        id.span().start,
+        if builtin.contains(id.get_ref().as_str()) {
+          Ok(())
+        } else {
+          Err(anyhow!("unknown built-in rule id `{}`", id.get_ref()))
+        },
+      );
+    }
+
+    for pattern in &config.exclude {
+      check(
+        pattern.span().start,
+        Exclude::new(&[pattern.get_ref()]).map(drop),
+      );
+    }
+
+    if let Some(directory) = config.trash.directory {
+      let offset = directory.span().start;
+
+      let trash = TrashConfig {
+        directory: Some(directory.into_inner()),
+        ..TrashConfig::default()
+      };
+
+      check(offset, trash.trash().map(drop));
+    }
+
+    let mut ids = HashSet::new();
+
+    for rule in config.rules {
+      let offset = rule.span().start;
+
+      let rule = rule.into_inner();
+
+      let id = rule.id.get_ref();
+
+      check(
+        rule.id.span().start,
+        if id.trim().is_empty() {
+          Err(anyhow!("rule id cannot be empty"))
+        } else if !ids.insert(id.clone()) {
+          Err(anyhow!("duplicate rule id `{id}`"))
+        } else {
+          Ok(())
+        },
+      );
+
+      check(
+        offset,
+        if rule.actions.is_empty() {
+          Err(anyhow!("rule actions cannot be empty"))
+        } else {
+          Ok(())
+        },
+      );
+
+      for action in rule.actions {
+        let offset = action.span().start;
+        check(offset, Action::try_from(action.into_inner()).map(drop));
+      }
+
+      let offset = rule.detection.span().start;
+
+      check(
+        offset,
+        Detection::try_from(rule.detection.into_inner()).map(drop),
+      );
+
+      for pattern in &rule.keep {
+        check(
+          pattern.span().start,
+          Keep::new(&[pattern.get_ref()]).map(drop),
+        );
+      }
+    }
+
+    errors
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, indoc::indoc};
+
+  fn validate(source: &str) -> Vec<((usize, usize), String)> {
+    ConfigCommand::validate(source)
+      .into_iter()
+      .map(|(offset, message)| {
+        (ConfigCommand::position(source, offset), message)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn starter_config_is_valid() {
+    let starter = ConfigCommand::starter();
+
+    assert!(ConfigCommand::validate(&starter).is_empty());
+
+    assert!(starter.contains("#   cargo"));
+  }
+
+  #[test]
+  fn validate_reports_syntax_errors() {
+    assert_eq!(
+      validate("[default]\ndisabled = [\n"),
+      vec![((2, 13), "unclosed array, expected `]`".to_owned())],
+    );
+  }
+
+  #[test]
+  fn validate_reports_invalid_globs() {
+    let source = indoc! {r#"
+      [[rules]]
+      id = "ok"
+      detection = "Makefile"
+      actions = [{ remove = "build" }]
+
+      [[rules]]
+      id = "broken"
+      detection = "Makefile"
+      actions = [{ remove = "build[" }]
+    "#};
+
+    let errors = validate(source);
+
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(errors[0].0, (9, 12));
+
+    assert!(errors[0].1.starts_with("invalid remove pattern `build[`"));
+  }
+
+  #[test]
+  fn validate_reports_duplicate_ids() {
+    let source = indoc! {r#"
+      [[rules]]
+      id = "make"
+      detection = "Makefile"
+      actions = [{ remove = "build" }]
+
+      [[rules]]
+      id = "make"
+      detection = "Makefile"
+      actions = [{ remove = "dist" }]
+    "#};
+
+    assert_eq!(
+      validate(source),
+      vec![((7, 6), "duplicate rule id `make`".to_owned())],
+    );
+  }
+
+  #[test]
+  fn validate_reports_invalid_settings() {
+    let source = indoc! {r#"
+      exclude = ["ok", "bad["]
+
+      [default]
+      disabled = ["cargo", "carg"]
+
+      [trash]
+      directory = "relative"
+    "#};
+
+    assert_eq!(
+      validate(source)
+        .into_iter()
+        .map(|(position, _)| position)
+        .collect::<Vec<_>>(),
+      vec![(4, 22), (1, 18), (7, 13)],
+    );
+  }
+
+  #[test]
+  fn position_is_one_based() {
+    assert_eq!(ConfigCommand::position("a\nbc", 0), (1, 1));
+    assert_eq!(ConfigCommand::position("a\nbc", 3), (2, 2));
+  }
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+#[group(required = true, multiple = false)]
+pub(crate) struct Restore {
+  #[arg(long, help = "Restore every item in the trash")]
+  all: bool,
+  #[arg(long, help = "List items in the trash instead of restoring them")]
+  list: bool,
+  #[arg(help = "Restore items that were trashed from at or below these paths")]
+  paths: Vec<PathBuf>,
+}
+
+impl Restore {
+  pub(crate) fn run(self, config: Config) -> Result {
+    let style = Style::stdout();
+
+    let trash = config.trash.trash()?;
+
+    if self.list {
+      for item in trash.items()? {
+        println!(
+          "{} ({}, trashed {})",
+          style.apply(CYAN, item.original.display()),
+          style.apply(GREEN, Bytes(item.size)),
+          style.apply(
+            DIM,
+            (SystemTime::UNIX_EPOCH + Duration::from_secs(item.trashed))
+              .format()
+          ),
+        );
+      }
+
+      return Ok(());
+    }
+
+    let paths = self
+      .paths
+      .iter()
+      .map(std::path::absolute)
+      .collect::<io::Result<Vec<_>>>()?;
+
+    let restored = trash.restore(|item| {
+      self.all || paths.iter().any(|path| item.original.starts_with(path))
+    })?;
+
+    ensure!(
+      self.all || !restored.is_empty(),
+      "no trashed items match the given paths"
+    );
+
+    for item in restored {
+      println!(
+        "{} {} ({})",
+        style.apply(BOLD, "Restored"),
+        style.apply(CYAN, item.original.display()),
+        style.apply(GREEN, Bytes(item.size)),
+      );
+    }
+
+    Ok(())
+  }
+}
@@ -3,14 +3,12 @@ use super::*;
 #[derive(Debug)]
 enum RuleSource {
   Builtin,
-  Custom,
   Disabled,
 }
 
 fn print_builtin_rule(style: Style, rule: &dyn Rule, source: RuleSource) {
   let status = match source {
     RuleSource::Builtin => style.apply(GREEN, "enabled"),
-    RuleSource::Custom => style.apply(YELLOW, "custom"),
     RuleSource::Disabled => style.apply(RED, "disabled"),
   };
 
@@ -21,13 +19,37 @@ fn print_builtin_rule(style: Style, rule: &dyn Rule, source: RuleSource) {
     status,
   );
 
-  println!("  {}: {}", style.apply(CYAN, "detection"), rule.detection(),);
+  println!("  {}: {}", style.apply(CYAN, "detection"), rule.detection());
+
+  if !rule.tags().is_empty() {
+    println!(
+      "  {}: {}",
+      style.apply(CYAN, "tags"),
+      rule.tags().join(", ")
+    );
+  }
+
+  if let Some(min_size) = rule.min_size() {
+    println!("  {}: {min_size}", style.apply(CYAN, "min size"));
+  }
+
+  if let Some(older_than) = rule.older_than() {
+    println!("  {}: {older_than}", style.apply(CYAN, "older than"));
+  }
 
   println!("  {}:", style.apply(CYAN, "actions"));
 
   for action in rule.actions() {
     println!("    {action}");
   }
+
+  if !rule.keep().is_empty() {
+    println!("  {}:", style.apply(CYAN, "keep"));
+
+    for pattern in rule.keep() {
+      println!("    {pattern}");
+    }
+  }
 }
 
 fn print_custom_rule(style: Style, rule: &config::RuleConfig) {
@@ -40,20 +62,38 @@ fn print_custom_rule(style: Style, rule: &config::RuleConfig) {
     style.apply(YELLOW, "custom"),
   );
 
-  println!("  {}: {}", style.apply(CYAN, "detection"), rule.detection,);
+  println!("  {}: {}", style.apply(CYAN, "detection"), rule.detection);
+
+  if !rule.tags.is_empty() {
+    println!("  {}: {}", style.apply(CYAN, "tags"), rule.tags.join(", "));
+  }
+
+  if let Some(min_size) = rule.min_size {
+    println!("  {}: {min_size}", style.apply(CYAN, "min size"));
+  }
+
+  if let Some(older_than) = rule.older_than {
+    println!("  {}: {older_than}", style.apply(CYAN, "older than"));
+  }
 
   println!("  {}:", style.apply(CYAN, "actions"));
 
   for action in &rule.actions {
     println!("    {action}");
   }
+
+  if !rule.keep.is_empty() {
+    println!("  {}:", style.apply(CYAN, "keep"));
+
+    for pattern in &rule.keep {
+      println!("    {pattern}");
+    }
+  }
 }
 
-pub(crate) fn run() -> Result {
+pub(crate) fn run(config: Config, selection: &Selection) -> Result {
   let style = Style::stdout();
 
-  let config = Config::load()?;
-
   let disabled = config
     .default_rules
     .disabled
@@ -61,11 +101,11 @@ pub(crate) fn run() -> Result {
     .cloned()
     .collect::<HashSet<_>>();
 
-  let custom_ids = config
+  let custom = config
     .rules
     .iter()
-    .map(|rule| rule.id.clone())
-    .collect::<HashSet<_>>();
+    .map(|rule| (rule.id.as_str(), rule))
+    .collect::<HashMap<_, _>>();
 
   let mut default_rules = Config::default_rules().collect::<Vec<_>>();
 
@@ -74,9 +114,19 @@ pub(crate) fn run() -> Result {
   for rule in &default_rules {
     let id = rule.id();
 
-    let source = if custom_ids.contains(id) {
-      RuleSource::Custom
-    } else if disabled.contains(id) {
+    if let Some(custom) = custom.get(id) {
+      if selection.selects(&custom.id, &custom.tags) {
+        print_custom_rule(style, custom);
+      }
+
+      continue;
+    }
+
+    if !selection.selects(id, rule.tags()) {
+      continue;
+    }
+
+    let source = if disabled.contains(id) {
       RuleSource::Disabled
     } else {
       RuleSource::Builtin
@@ -91,6 +141,7 @@ pub(crate) fn run() -> Result {
     .rules
     .iter()
     .filter(|rule| !default_ids.contains(rule.id.as_str()))
+    .filter(|rule| selection.selects(&rule.id, &rule.tags))
     .collect::<Vec<_>>();
 
   new_custom_rules.sort_by(|a, b| a.id.cmp(&b.id));
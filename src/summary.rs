@@ -0,0 +1,125 @@
+use super::*;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Summary {
+  pub(crate) bytes: u64,
+  pub(crate) dry_run: bool,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub(crate) failures: Vec<Failure>,
+  pub(crate) projects: u64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) reclaimed: Option<u64>,
+  pub(crate) trash: bool,
+}
+
+impl Display for Summary {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let (projects_label, bytes_label) = if self.dry_run {
+      ("Projects matched", "Bytes matched")
+    } else if self.trash {
+      ("Projects cleaned", "Bytes trashed")
+    } else {
+      ("Projects cleaned", "Bytes deleted")
+    };
+
+    let style = Style::stdout();
+
+    writeln!(
+      f,
+      "{}: {}, {}: {}",
+      style.apply(BOLD, projects_label),
+      style.apply(CYAN, self.projects),
+      style.apply(BOLD, bytes_label),
+      style.apply(GREEN, Bytes(self.bytes)),
+    )?;
+
+    if let Some(reclaimed) = self.reclaimed {
+      write!(
+        f,
+        "{}: {}",
+        style.apply(BOLD, "Space reclaimed"),
+        style.apply(GREEN, Bytes(reclaimed)),
+      )?;
+
+      if reclaimed != self.bytes {
+        let (difference, direction) = if reclaimed < self.bytes {
+          (self.bytes - reclaimed, "less")
+        } else {
+          (reclaimed - self.bytes, "more")
+        };
+
+        write!(
+          f,
+          " {}",
+          style.apply(
+            YELLOW,
+            format_args!("({} {direction} than estimated)", Bytes(difference))
+          ),
+        )?;
+      }
+
+      writeln!(f)?;
+    }
+
+    if !self.failures.is_empty() {
+      writeln!(
+        f,
+        "{}: {}",
+        style.apply(BOLD, "Failures"),
+        style.apply(RED, self.failures.len()),
+      )?;
+
+      for failure in &self.failures {
+        write!(f, "{failure}")?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn summary(bytes: u64, reclaimed: Option<u64>) -> String {
+    Summary {
+      bytes,
+      dry_run: false,
+      failures: Vec::new(),
+      projects: 1,
+      reclaimed,
+      trash: false,
+    }
+    .to_string()
+  }
+
+  #[test]
+  fn reclaimed_space_is_omitted_when_not_measured() {
+    assert_eq!(
+      summary(1024, None),
+      "Projects cleaned: 1, Bytes deleted: 1 KiB\n"
+    );
+  }
+
+  #[test]
+  fn reclaimed_space_matching_the_estimate() {
+    assert_eq!(
+      summary(1024, Some(1024)),
+      "Projects cleaned: 1, Bytes deleted: 1 KiB\nSpace reclaimed: 1 KiB\n",
+    );
+  }
+
+  #[test]
+  fn reclaimed_space_differing_from_the_estimate() {
+    assert_eq!(
+      summary(4096, Some(1024)),
+      "Projects cleaned: 1, Bytes deleted: 4 KiB\nSpace reclaimed: 1 KiB (3 KiB less than estimated)\n",
+    );
+
+    assert_eq!(
+      summary(1024, Some(4096)),
+      "Projects cleaned: 1, Bytes deleted: 1 KiB\nSpace reclaimed: 4 KiB (3 KiB more than estimated)\n",
+    );
+  }
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+/// What cleaning did, gathered from the worker pool as tasks finish.
+#[derive(Debug, Default)]
+pub(crate) struct Tally {
+  cleaned: BTreeMap<PathBuf, u64>,
+  pub(crate) failures: Vec<Failure>,
+}
+
+impl Tally {
+  pub(crate) fn bytes(&self) -> u64 {
+    self.cleaned.values().sum()
+  }
+
+  /// Counts the project at `root` as cleaned, crediting it with `bytes`.
+  pub(crate) fn clean(&mut self, root: &Path, bytes: u64) {
+    *self.cleaned.entry(root.to_path_buf()).or_default() += bytes;
+  }
+
+  pub(crate) fn projects(&self) -> u64 {
+    u64::try_from(self.cleaned.len()).unwrap_or(u64::MAX)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn projects_are_counted_once() {
+    let mut tally = Tally::default();
+
+    tally.clean(Path::new("/a"), 10);
+    tally.clean(Path::new("/a"), 5);
+    tally.clean(Path::new("/b"), 0);
+
+    assert_eq!(tally.bytes(), 15);
+    assert_eq!(tally.projects(), 2);
+  }
+}
@@ -2,41 +2,34 @@ use super::*;
 
 #[derive(Debug)]
 pub(crate) enum Task {
-  Command(&'static str),
+  Command(&'static Invocation),
   Remove { path: PathBuf, size: u64 },
 }
 
-impl Task {
-  fn command(command: &str, context: &Context) -> Result {
-    let command_text = command.trim();
-
-    ensure!(!command_text.is_empty(), "command action cannot be empty");
-
-    let mut command = if cfg!(windows) {
-      let mut command = Command::new("cmd");
-      command.arg("/C").arg(command_text);
-      command
-    } else {
-      let mut command = Command::new("sh");
-      command.arg("-c").arg(command_text);
-      command
-    };
-
-    let status = command.current_dir(&context.root).status()?;
-
-    ensure!(
-      status.success(),
-      "command `{}` failed in `{}`",
-      command_text,
-      context.root.display()
-    );
-
-    Ok(())
+impl Serialize for Task {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Self::Command(command) => {
+        let mut state = serializer.serialize_struct("Task", 2)?;
+        state.serialize_field("type", "command")?;
+        state.serialize_field("command", &command.to_string())?;
+        state.end()
+      }
+      Self::Remove { path, size } => {
+        let mut state = serializer.serialize_struct("Task", 3)?;
+        state.serialize_field("type", "remove")?;
+        state.serialize_field("path", path)?;
+        state.serialize_field("size", size)?;
+        state.end()
+      }
+    }
   }
+}
 
+impl Task {
   pub(crate) fn execute(&self, context: &Context) -> Result {
     match self {
-      Task::Command(command) => Self::command(command, context),
+      Task::Command(command) => command.run(&context.root).map(drop),
       Task::Remove { path, .. } => Self::remove(context, path),
     }
   }
@@ -76,8 +69,32 @@ impl Task {
     }
   }
 
+  /// Removes a directory, handing each of its top-level children to a worker.
   fn remove_directory(path: &Path) -> Result {
-    match fs::remove_dir_all(path) {
+    match fs::symlink_metadata(path) {
+      Ok(metadata) if metadata.file_type().is_symlink() => {
+        return Self::remove_tree(path);
+      }
+      Ok(_) => {}
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+      Err(e) => return Err(e.into()),
+    }
+
+    let entries = match fs::read_dir(path) {
+      Ok(entries) => entries.collect::<io::Result<Vec<_>>>()?,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+      Err(e) => return Err(e.into()),
+    };
+
+    entries.into_par_iter().try_for_each(|entry| {
+      if entry.file_type()?.is_file() {
+        Self::remove_file(&entry.path())
+      } else {
+        Self::remove_tree(&entry.path())
+      }
+    })?;
+
+    match fs::remove_dir(path) {
       Ok(()) => Ok(()),
       Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
       Err(e) => Err(e.into()),
@@ -91,17 +108,34 @@ impl Task {
       Err(e) => Err(e.into()),
     }
   }
+
+  fn remove_tree(path: &Path) -> Result {
+    match fs::remove_dir_all(path) {
+      Ok(()) => Ok(()),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+      Err(e) => Err(e.into()),
+    }
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use {super::*, tempfile::tempdir};
+  use {super::*, tempfile::tempdir, temptree::temptree};
 
   #[test]
   fn remove_is_idempotent_for_missing_paths() {
     let tempdir = tempdir().unwrap();
 
-    let context = Context::new(tempdir.path().to_path_buf(), false).unwrap();
+    let index = Index::new(
+      tempdir.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
 
     let file_task = Task::Remove {
       path: PathBuf::from("stale.log"),
@@ -117,4 +151,36 @@ mod tests {
 
     directory_task.execute(&context).unwrap();
   }
+
+  #[test]
+  fn remove_deletes_directory_trees() {
+    let tree = temptree! {
+      "node_modules": {
+        "left-pad": {
+          "index.js": "x",
+        },
+        "package-lock.json": "x",
+      },
+    };
+
+    let index = Index::new(
+      tree.path().to_path_buf(),
+      AgeSource::Files,
+      false,
+      None,
+      &[],
+    )
+    .unwrap();
+
+    let context = index.context(Path::new("")).unwrap();
+
+    Task::Remove {
+      path: PathBuf::from("node_modules"),
+      size: 0,
+    }
+    .execute(&context)
+    .unwrap();
+
+    assert!(!tree.path().join("node_modules").exists());
+  }
 }
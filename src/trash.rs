@@ -0,0 +1,506 @@
+use super::*;
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct Item {
+  pub(crate) id: String,
+  pub(crate) location: PathBuf,
+  pub(crate) original: PathBuf,
+  pub(crate) size: u64,
+  pub(crate) trashed: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Trash {
+  counter: AtomicU64,
+  directory: PathBuf,
+}
+
+impl Trash {
+  pub(crate) const DIRECTORY: &'static str = ".swab-trash";
+
+  pub(crate) fn default_directory() -> Result<PathBuf> {
+    let strategy = etcetera::choose_app_strategy(AppStrategyArgs {
+      top_level_domain: "rs".into(),
+      author: String::new(),
+      app_name: "swab".into(),
+    })?;
+
+    Ok(strategy.data_dir().join("trash"))
+  }
+
+  fn delete(path: &Path) -> Result {
+    let result = match fs::symlink_metadata(path) {
+      Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+      Ok(_) => fs::remove_file(path),
+      Err(error) => Err(error),
+    };
+
+    match result {
+      Ok(()) => Ok(()),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  /// The trash on the same filesystem as `path`: the configured one if it is,
+  /// otherwise the first writable `.swab-trash` at the top of that filesystem
+  /// or in the scanned `root`.
+  fn destination(&self, path: &Path, root: &Path) -> Result<PathBuf> {
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+
+      fs::create_dir_all(&self.directory)?;
+
+      let device = fs::symlink_metadata(path)?.dev();
+
+      if fs::metadata(&self.directory)?.dev() == device {
+        return Ok(self.directory.clone());
+      }
+
+      let top = path
+        .ancestors()
+        .skip(1)
+        .take_while(|ancestor| {
+          fs::metadata(ancestor).is_ok_and(|metadata| metadata.dev() == device)
+        })
+        .last()
+        .unwrap_or(path);
+
+      let candidates = [top, root].map(|parent| parent.join(Self::DIRECTORY));
+
+      for candidate in &candidates {
+        if candidate.parent().is_some_and(|parent| {
+          fs::metadata(parent).is_ok_and(|metadata| metadata.dev() == device)
+        }) && fs::create_dir_all(candidate.join("items")).is_ok()
+        {
+          return Ok(candidate.clone());
+        }
+      }
+
+      bail!(
+        "no writable trash on the filesystem of `{}`: cannot create `{}` or `{}`",
+        path.display(),
+        candidates[0].display(),
+        candidates[1].display(),
+      )
+    }
+
+    #[cfg(not(unix))]
+    {
+      let _ = (path, root);
+      Ok(self.directory.clone())
+    }
+  }
+
+  pub(crate) fn items(&self) -> Result<Vec<Item>> {
+    let contents = match fs::read_to_string(self.manifest()) {
+      Ok(contents) => contents,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => {
+        return Ok(Vec::new());
+      }
+      Err(error) => return Err(error.into()),
+    };
+
+    contents
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .map(|line| Ok(serde_json::from_str(line)?))
+      .collect()
+  }
+
+  /// Locks the manifest against other threads and swab processes until the
+  /// returned file is dropped.
+  fn lock(&self) -> Result<fs::File> {
+    fs::create_dir_all(&self.directory)?;
+
+    let file = fs::OpenOptions::new()
+      .create(true)
+      .truncate(false)
+      .write(true)
+      .open(self.directory.join("manifest.lock"))?;
+
+    file.lock()?;
+
+    Ok(file)
+  }
+
+  fn manifest(&self) -> PathBuf {
+    self.directory.join("manifest.jsonl")
+  }
+
+  pub(crate) fn new(directory: PathBuf) -> Self {
+    Self {
+      counter: AtomicU64::new(0),
+      directory,
+    }
+  }
+
+  /// Permanently deletes every item trashed more than `age` ago, returning
+  /// the items deleted and the errors for those that couldn't be, which stay
+  /// in the trash.
+  pub(crate) fn purge(&self, age: Age) -> Result<(Vec<Item>, Vec<Error>)> {
+    let _lock = self.lock()?;
+
+    let (expired, mut kept): (Vec<Item>, Vec<Item>) =
+      self.items()?.into_iter().partition(|item| {
+        age.older_than(
+          SystemTime::UNIX_EPOCH + Duration::from_secs(item.trashed),
+        )
+      });
+
+    let (mut purged, mut errors) = (Vec::new(), Vec::new());
+
+    for item in expired {
+      match Self::delete(&self.directory.join(&item.location)) {
+        Ok(()) => purged.push(item),
+        Err(error) => {
+          errors.push(error.context(format!(
+            "failed to purge `{}` from the trash",
+            item.original.display()
+          )));
+          kept.push(item);
+        }
+      }
+    }
+
+    self.write(&kept)?;
+
+    Ok((purged, errors))
+  }
+
+  /// Moves `path` into the trash and records it in the manifest.
+  pub(crate) fn put(&self, path: &Path, root: &Path, size: u64) -> Result {
+    let original = std::path::absolute(path)?;
+
+    if let Err(error) = fs::symlink_metadata(&original) {
+      if error.kind() == io::ErrorKind::NotFound {
+        return Ok(());
+      }
+
+      return Err(error.into());
+    }
+
+    let trashed = SystemTime::now().timestamp();
+
+    let id = format!(
+      "{trashed}-{}-{}",
+      process::id(),
+      self.counter.fetch_add(1, atomic::Ordering::Relaxed)
+    );
+
+    let destination = self.destination(&original, root)?;
+
+    let location = if destination == self.directory {
+      Path::new("items").join(&id)
+    } else {
+      destination.join("items").join(&id)
+    };
+
+    Self::relocate(&original, &self.directory.join(&location))?;
+
+    let item = Item {
+      id,
+      location,
+      original,
+      size,
+      trashed,
+    };
+
+    let _lock = self.lock()?;
+
+    let mut manifest = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(self.manifest())?;
+
+    writeln!(manifest, "{}", serde_json::to_string(&item)?)?;
+
+    Ok(())
+  }
+
+  fn relocate(source: &Path, destination: &Path) -> Result {
+    if let Some(parent) = destination.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    match fs::rename(source, destination) {
+      Ok(()) => Ok(()),
+      Err(error) if error.kind() == io::ErrorKind::CrossesDevices => bail!(
+        "refusing to copy `{}` to `{}` across filesystems",
+        source.display(),
+        destination.display()
+      ),
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  /// Moves every item selected by `filter` back to where it came from.
+  pub(crate) fn restore(
+    &self,
+    filter: impl Fn(&Item) -> bool,
+  ) -> Result<Vec<Item>> {
+    let _lock = self.lock()?;
+
+    let (selected, mut kept): (Vec<Item>, Vec<Item>) =
+      self.items()?.into_iter().partition(filter);
+
+    let mut restored = Vec::new();
+
+    let mut result = Ok(());
+
+    for item in selected {
+      if result.is_err() {
+        kept.push(item);
+        continue;
+      }
+
+      if fs::symlink_metadata(&item.original).is_ok() {
+        result = Err(anyhow!(
+          "cannot restore `{}`: path already exists",
+          item.original.display()
+        ));
+        kept.push(item);
+        continue;
+      }
+
+      match Self::relocate(&self.directory.join(&item.location), &item.original)
+      {
+        Ok(()) => restored.push(item),
+        Err(error) => {
+          result = Err(error);
+          kept.push(item);
+        }
+      }
+    }
+
+    self.write(&kept)?;
+
+    result.map(|()| restored)
+  }
+
+  fn write(&self, items: &[Item]) -> Result {
+    fs::create_dir_all(&self.directory)?;
+
+    let contents = items.iter().try_fold(String::new(), |mut acc, item| {
+      acc.push_str(&serde_json::to_string(item)?);
+      acc.push('\n');
+      Ok::<_, Error>(acc)
+    })?;
+
+    fs::write(self.manifest(), contents)?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  #[test]
+  fn put_moves_path_into_trash() {
+    let tree = temptree! {
+      "project": {
+        "target": {
+          "app": "x",
+        },
+      },
+    };
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("project/target"), tree.path(), 1)
+      .unwrap();
+
+    assert!(!tree.path().join("project/target").exists());
+
+    let items = trash.items().unwrap();
+
+    assert_eq!(items.len(), 1);
+
+    assert_eq!(items[0].original, tree.path().join("project/target"));
+
+    assert_eq!(items[0].size, 1);
+
+    assert!(
+      tree
+        .path()
+        .join("trash")
+        .join(&items[0].location)
+        .join("app")
+        .exists()
+    );
+  }
+
+  #[test]
+  fn put_ignores_missing_paths() {
+    let tree = temptree! {};
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("missing"), tree.path(), 0)
+      .unwrap();
+
+    assert!(trash.items().unwrap().is_empty());
+  }
+
+  #[test]
+  fn restore_moves_items_back() {
+    let tree = temptree! {
+      "a": {
+        "node_modules": {
+          "index.js": "x",
+        },
+      },
+      "b": {
+        "target": {
+          "app": "x",
+        },
+      },
+    };
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("a/node_modules"), tree.path(), 1)
+      .unwrap();
+    trash
+      .put(&tree.path().join("b/target"), tree.path(), 1)
+      .unwrap();
+
+    let restored = trash
+      .restore(|item| item.original.starts_with(tree.path().join("a")))
+      .unwrap();
+
+    assert_eq!(restored.len(), 1);
+
+    assert!(tree.path().join("a/node_modules/index.js").exists());
+
+    assert!(!tree.path().join("b/target").exists());
+
+    assert_eq!(trash.items().unwrap().len(), 1);
+  }
+
+  #[test]
+  fn restore_refuses_to_overwrite() {
+    let tree = temptree! {
+      "target": {
+        "app": "x",
+      },
+    };
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("target"), tree.path(), 1)
+      .unwrap();
+
+    fs::create_dir(tree.path().join("target")).unwrap();
+
+    assert_eq!(
+      trash.restore(|_| true).unwrap_err().to_string(),
+      format!(
+        "cannot restore `{}`: path already exists",
+        tree.path().join("target").display()
+      ),
+    );
+
+    assert_eq!(trash.items().unwrap().len(), 1);
+  }
+
+  #[test]
+  fn purge_removes_expired_items() {
+    let tree = temptree! {
+      "target": {
+        "app": "x",
+      },
+      "dist": {
+        "app": "x",
+      },
+    };
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("target"), tree.path(), 1)
+      .unwrap();
+    trash
+      .put(&tree.path().join("dist"), tree.path(), 1)
+      .unwrap();
+
+    let mut items = trash.items().unwrap();
+
+    items[0].trashed = 0;
+
+    trash.write(&items).unwrap();
+
+    let (expired, errors) = trash.purge(Age(Duration::from_hours(24))).unwrap();
+
+    assert!(errors.is_empty());
+
+    assert_eq!(expired.len(), 1);
+
+    assert!(
+      !tree
+        .path()
+        .join("trash")
+        .join(&expired[0].location)
+        .exists()
+    );
+
+    assert_eq!(trash.items().unwrap(), vec![items.remove(1)]);
+  }
+
+  #[test]
+  fn purge_keeps_going_past_failed_deletes() {
+    let tree = temptree! {
+      "blocker": "",
+      "target": {
+        "app": "x",
+      },
+    };
+
+    let trash = Trash::new(tree.path().join("trash"));
+
+    trash
+      .put(&tree.path().join("target"), tree.path(), 1)
+      .unwrap();
+
+    let mut items = trash.items().unwrap();
+
+    items[0].trashed = 0;
+
+    items.insert(
+      0,
+      Item {
+        id: "broken".into(),
+        location: tree.path().join("blocker/child"),
+        original: tree.path().join("dist"),
+        size: 1,
+        trashed: 0,
+      },
+    );
+
+    trash.write(&items).unwrap();
+
+    let (expired, errors) = trash.purge(Age(Duration::from_hours(24))).unwrap();
+
+    assert_eq!(expired, vec![items.remove(1)]);
+
+    assert_eq!(
+      errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>(),
+      vec![format!(
+        "failed to purge `{}` from the trash",
+        tree.path().join("dist").display()
+      )],
+    );
+
+    assert_eq!(trash.items().unwrap(), items);
+  }
+}
@@ -0,0 +1,148 @@
+use super::*;
+
+/// How much space paths take up, counting hard links once.
+#[derive(Debug)]
+pub(crate) struct Usage<'a> {
+  apparent: bool,
+  exclude: &'a Exclude,
+  /// The measured paths found to hold an excluded path.
+  excluded: Mutex<HashSet<PathBuf>>,
+  /// The hard-linked files counted below each measured path, and their size.
+  #[cfg_attr(not(unix), allow(dead_code))]
+  links: Mutex<HashMap<PathBuf, HashMap<(u64, u64), u64>>>,
+}
+
+impl<'a> Usage<'a> {
+  /// The number of bytes the entry described by `metadata`, found while
+  /// measuring `root`, accounts for.
+  pub(crate) fn bytes(&self, root: &Path, metadata: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    if !self.apparent {
+      use std::os::unix::fs::MetadataExt;
+
+      let bytes = metadata.blocks() * 512;
+
+      if !metadata.is_dir() && metadata.nlink() > 1 {
+        let mut links =
+          self.links.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let seen = links.entry(root.to_path_buf()).or_default();
+
+        if seen.contains_key(&(metadata.dev(), metadata.ino())) {
+          return 0;
+        }
+
+        seen.insert((metadata.dev(), metadata.ino()), bytes);
+      }
+
+      return bytes;
+    }
+
+    let _ = root;
+
+    if metadata.is_dir() { 0 } else { metadata.len() }
+  }
+
+  /// Notes that `root` holds an excluded path if `path`, found while
+  /// measuring it, is one.
+  pub(crate) fn check(&self, root: &Path, path: &Path) {
+    if path != root && self.exclude.matches(path) {
+      self
+        .excluded
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(root.to_path_buf());
+    }
+  }
+
+  /// Whether measuring `root` came across an excluded path.
+  pub(crate) fn contains_excluded(&self, root: &Path) -> bool {
+    self
+      .excluded
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .contains(root)
+  }
+
+  pub(crate) fn new(apparent: bool, exclude: &'a Exclude) -> Self {
+    Self {
+      apparent,
+      exclude,
+      excluded: Mutex::default(),
+      links: Mutex::default(),
+    }
+  }
+
+  /// The bytes `root` counted for hard-linked files already in `seen`, which
+  /// it then adds its own to.
+  pub(crate) fn shared(
+    &self,
+    root: &Path,
+    seen: &mut HashSet<(u64, u64)>,
+  ) -> u64 {
+    self
+      .links
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(root)
+      .into_iter()
+      .flatten()
+      .filter(|(inode, _)| !seen.insert(**inode))
+      .map(|(_, bytes)| bytes)
+      .sum()
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use {super::*, temptree::temptree};
+
+  #[test]
+  fn hard_links_are_counted_once() {
+    let tree = temptree! {};
+
+    fs::write(tree.path().join("a"), "x".repeat(10_000)).unwrap();
+
+    fs::hard_link(tree.path().join("a"), tree.path().join("b")).unwrap();
+
+    let exclude = Exclude::default();
+
+    let usage = Usage::new(false, &exclude);
+
+    let bytes = |root: &str, path: &str| {
+      usage.bytes(
+        &tree.path().join(root),
+        &fs::metadata(tree.path().join(path)).unwrap(),
+      )
+    };
+
+    assert!(bytes("", "a") >= 10_000);
+    assert_eq!(bytes("", "b"), 0);
+
+    assert!(bytes("b", "b") >= 10_000);
+
+    let mut seen = HashSet::new();
+
+    assert_eq!(usage.shared(&tree.path().join(""), &mut seen), 0);
+    assert!(usage.shared(&tree.path().join("b"), &mut seen) >= 10_000);
+  }
+
+  #[test]
+  fn sparse_files_count_allocated_blocks() {
+    let tree = temptree! {};
+
+    let path = tree.path().join("sparse");
+
+    fs::File::create(&path).unwrap().set_len(1 << 30).unwrap();
+
+    let metadata = fs::metadata(&path).unwrap();
+
+    assert_eq!(
+      Usage::new(true, &Exclude::default()).bytes(&path, &metadata),
+      1 << 30
+    );
+    assert!(
+      Usage::new(false, &Exclude::default()).bytes(&path, &metadata) < 1 << 20
+    );
+  }
+}
@@ -4,6 +4,7 @@ use {
   filetime::{self, FileTime},
   indoc::indoc,
   pretty_assertions::assert_eq,
+  regex::Regex,
   std::{
     fs, iter::once, process::Command, str, time::Duration, time::SystemTime,
   },
@@ -14,21 +15,31 @@ type Result<T = (), E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug)]
 struct Test<'a> {
-  age: Option<Duration>,
+  allocated: bool,
   arguments: Vec<String>,
   directory: Option<String>,
+  env: Vec<(&'a str, &'a str)>,
   exists: Vec<&'a str>,
   expected_status: i32,
   expected_stderr: String,
   expected_stdout: String,
   files: Vec<(&'a str, &'a str)>,
+  links: Vec<(&'a str, &'a str)>,
+  mtime: Option<SystemTime>,
   tempdir: TempDir,
+  tracked: Vec<&'a str>,
 }
 
 impl<'a> Test<'a> {
   fn age(self, age: Duration) -> Self {
+    self.mtime(SystemTime::now() - age)
+  }
+
+  /// Measures allocated blocks, which depend on the filesystem, rather than
+  /// the apparent sizes every other test compares.
+  fn allocated(self) -> Self {
     Self {
-      age: Some(age),
+      allocated: true,
       ..self
     }
   }
@@ -52,12 +63,23 @@ impl<'a> Test<'a> {
       .env("RUST_BACKTRACE", "0")
       .current_dir(&self.tempdir);
 
+    for (key, value) in &self.env {
+      command.env(
+        key,
+        value.replace("[ROOT]", &self.tempdir.path().display().to_string()),
+      );
+    }
+
     if let Some(dir) = &self.directory {
       command.arg(self.tempdir.path().join(dir));
     } else {
       command.arg(self.tempdir.path());
     }
 
+    if !self.allocated {
+      command.arg("--apparent-size");
+    }
+
     command.args(&self.arguments);
 
     Ok(command)
@@ -70,6 +92,13 @@ impl<'a> Test<'a> {
     }
   }
 
+  fn env(self, key: &'a str, value: &'a str) -> Self {
+    Self {
+      env: self.env.into_iter().chain(once((key, value))).collect(),
+      ..self
+    }
+  }
+
   fn exists(self, paths: &[&'a str]) -> Self {
     Self {
       exists: self
@@ -113,17 +142,39 @@ impl<'a> Test<'a> {
     }
   }
 
+  fn link(self, original: &'a str, link: &'a str) -> Self {
+    Self {
+      links: self
+        .links
+        .into_iter()
+        .chain(once((original, link)))
+        .collect(),
+      ..self
+    }
+  }
+
+  fn mtime(self, mtime: SystemTime) -> Self {
+    Self {
+      mtime: Some(mtime),
+      ..self
+    }
+  }
+
   fn new() -> Result<Self> {
     Ok(Self {
-      age: None,
+      allocated: false,
       arguments: Vec::new(),
       directory: None,
+      env: Vec::new(),
       exists: Vec::new(),
       expected_status: 0,
       expected_stderr: String::new(),
       expected_stdout: String::new(),
       files: Vec::new(),
+      links: Vec::new(),
+      mtime: None,
       tempdir: TempDir::with_prefix("swab-test")?,
+      tracked: Vec::new(),
     })
   }
 
@@ -138,8 +189,48 @@ impl<'a> Test<'a> {
       fs::write(&full_path, content)?;
     }
 
-    if let Some(age) = self.age {
-      let mtime = FileTime::from_system_time(SystemTime::now() - age);
+    for (original, link) in &self.links {
+      let full_path = self.tempdir.path().join(link);
+
+      if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)?;
+      }
+
+      fs::hard_link(self.tempdir.path().join(original), full_path)?;
+    }
+
+    if !self.tracked.is_empty() {
+      let git = |arguments: &[&str]| -> Result {
+        let status = Command::new("git")
+          .args(arguments)
+          .current_dir(&self.tempdir)
+          .status()?;
+
+        assert!(status.success(), "git {arguments:?} failed");
+
+        Ok(())
+      };
+
+      git(&["init", "--quiet"])?;
+
+      git(&[&["add", "--"], self.tracked.as_slice()].concat())?;
+
+      git(&[
+        "-c",
+        "user.name=swab",
+        "-c",
+        "user.email=swab@example.com",
+        "-c",
+        "commit.gpgsign=false",
+        "commit",
+        "--quiet",
+        "--message",
+        "initial",
+      ])?;
+    }
+
+    if let Some(mtime) = self.mtime {
+      let mtime = FileTime::from_system_time(mtime);
 
       for (path, _) in &self.files {
         let full_path = self.tempdir.path().join(path);
@@ -176,6 +267,12 @@ impl<'a> Test<'a> {
       .replace(&self.tempdir.path().display().to_string(), "[ROOT]")
       .replace('\\', "/");
 
+    // Free space depends on whatever else is using the filesystem.
+    let stdout = Regex::new(
+      r#"(?m)(^Space reclaimed: ).*$|("(?:available|reclaimed)": ?)\d+"#,
+    )?
+    .replace_all(&stdout, "$1$2[SIZE]");
+
     assert_eq!(stdout, self.expected_stdout);
 
     let created = self.files.iter().map(|(path, _)| *path).collect::<Vec<_>>();
@@ -203,6 +300,17 @@ impl<'a> Test<'a> {
 
     Ok(())
   }
+
+  fn tracked(self, paths: &[&'a str]) -> Self {
+    Self {
+      tracked: self
+        .tracked
+        .into_iter()
+        .chain(paths.iter().copied())
+        .collect(),
+      ..self
+    }
+  }
 }
 
 #[test]
@@ -218,6 +326,7 @@ fn cargo_removes_target_directory() -> Result {
       [ROOT]/project Cargo project (0 seconds ago)
         └─ target (1.46 KiB)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -236,6 +345,7 @@ fn cargo_removes_target_directory_at_root() -> Result {
       [ROOT] Cargo project (0 seconds ago)
         └─ target (1.46 KiB)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -263,6 +373,7 @@ fn cargo_removes_nested_target_directories() -> Result {
         ├─ crates/foo/target (500 bytes)
         └─ target (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1.95 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -282,6 +393,7 @@ fn dotnet_removes_bin_and_obj() -> Result {
         ├─ bin (1000 bytes)
         └─ obj (500 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -304,6 +416,7 @@ fn elixir_removes_build_directories() -> Result {
         ├─ .elixir_ls (500 bytes)
         └─ _build (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -326,6 +439,7 @@ fn gradle_removes_build_directories() -> Result {
         ├─ .gradle (500 bytes)
         └─ build (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -343,6 +457,7 @@ fn gradle_kotlin_dsl() -> Result {
       [ROOT]/project Gradle project (0 seconds ago)
         └─ build (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -363,6 +478,7 @@ fn maven_removes_target() -> Result {
       [ROOT]/project Maven project (0 seconds ago)
         └─ target (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -381,6 +497,7 @@ fn node_removes_node_modules() -> Result {
       [ROOT]/project Node project (0 seconds ago)
         └─ node_modules (1.46 KiB)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -398,6 +515,7 @@ fn node_removes_angular_cache() -> Result {
       [ROOT]/project Node project (0 seconds ago)
         └─ .angular (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -426,6 +544,7 @@ fn python_removes_cache_directories() -> Result {
         ├─ .venv (1000 bytes)
         └─ __pycache__ (500 bytes)
       Projects cleaned: 1, Bytes deleted: 1.86 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -445,6 +564,7 @@ fn swift_removes_build_directories() -> Result {
         ├─ .build (1000 bytes)
         └─ .swiftpm (500 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -464,6 +584,7 @@ fn zig_removes_cache_directories() -> Result {
         ├─ zig-cache (1000 bytes)
         └─ zig-out (500 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -484,6 +605,7 @@ fn cabal_removes_dist_newstyle() -> Result {
       [ROOT]/project Cabal (Haskell) project (0 seconds ago)
         └─ dist-newstyle (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -505,6 +627,7 @@ fn cmake_removes_build_directories() -> Result {
         ├─ cmake-build-debug (500 bytes)
         └─ cmake-build-release (500 bytes)
       Projects cleaned: 1, Bytes deleted: 1.95 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -523,6 +646,7 @@ fn composer_removes_vendor() -> Result {
       [ROOT]/project Composer (PHP) project (0 seconds ago)
         └─ vendor (1.46 KiB)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -540,6 +664,7 @@ fn godot_removes_godot_directory() -> Result {
       [ROOT]/project Godot 4 project (0 seconds ago)
         └─ .godot (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -560,6 +685,7 @@ fn jupyter_removes_checkpoints() -> Result {
       [ROOT] Jupyter project (0 seconds ago)
         └─ project/.ipynb_checkpoints (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -577,6 +703,7 @@ fn pixi_removes_pixi_directory() -> Result {
       [ROOT]/project Pixi project (0 seconds ago)
         └─ .pixi (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -606,6 +733,7 @@ fn pub_removes_build_directories() -> Result {
         ├─ linux/flutter/ephemeral (300 bytes)
         └─ windows/flutter/ephemeral (200 bytes)
       Projects cleaned: 1, Bytes deleted: 1.95 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -631,6 +759,7 @@ fn sbt_removes_target_directories() -> Result {
         ├─ project/target (500 bytes)
         └─ target (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -651,6 +780,7 @@ fn stack_removes_stack_work() -> Result {
       [ROOT]/project Stack (Haskell) project (0 seconds ago)
         └─ .stack-work (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -668,6 +798,7 @@ fn turborepo_removes_turbo_directory() -> Result {
       [ROOT]/project Turborepo project (0 seconds ago)
         └─ .turbo (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -700,6 +831,7 @@ fn unity_removes_build_directories() -> Result {
         ├─ Obj (300 bytes)
         └─ Temp (500 bytes)
       Projects cleaned: 1, Bytes deleted: 2.25 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -728,6 +860,7 @@ fn unreal_removes_build_directories() -> Result {
         ├─ Intermediate (100 bytes)
         └─ Saved (300 bytes)
       Projects cleaned: 1, Bytes deleted: 2.05 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -772,6 +905,7 @@ fn no_matching_projects() -> Result {
     .expected_stdout(indoc! {
       "
       Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -801,6 +935,7 @@ fn multiple_projects_different_rules() -> Result {
       [ROOT]/rust-app Cargo project (0 seconds ago)
         └─ target (1000 bytes)
       Projects cleaned: 3, Bytes deleted: 1.76 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -830,6 +965,7 @@ fn multiple_projects_same_rule() -> Result {
       [ROOT]/shared Node project (0 seconds ago)
         └─ node_modules (300 bytes)
       Projects cleaned: 3, Bytes deleted: 1.76 KiB
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -847,6 +983,7 @@ fn older_than_filters_recent_projects() -> Result {
     .expected_stdout(indoc! {
       "
       Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -857,7 +994,7 @@ fn older_than_includes_old_projects() -> Result {
   Test::new()?
     .argument("--older-than")
     .argument("7d")
-    .age(Duration::from_secs(60 * 60 * 24 * 30))
+    .age(Duration::from_hours(24 * 30))
     .file("project/Cargo.toml", "")
     .file("project/target/debug/app", &"a".repeat(1000))
     .exists(&["project/Cargo.toml"])
@@ -867,6 +1004,7 @@ fn older_than_includes_old_projects() -> Result {
       [ROOT]/project Cargo project (30 days ago)
         └─ target (1000 bytes)
       Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
@@ -877,7 +1015,7 @@ fn older_than_with_ago_suffix() -> Result {
   Test::new()?
     .argument("--older-than")
     .argument("1w ago")
-    .age(Duration::from_secs(60 * 60 * 24 * 14))
+    .age(Duration::from_hours(24 * 14))
     .file("project/package.json", "")
     .file("project/node_modules/foo/index.js", &"a".repeat(500))
     .exists(&["project/package.json"])
@@ -887,11 +1025,123 @@ fn older_than_with_ago_suffix() -> Result {
       [ROOT]/project Node project (14 days ago)
         └─ node_modules (500 bytes)
       Projects cleaned: 1, Bytes deleted: 500 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn older_than_accepts_dates() -> Result {
+  Test::new()?
+    .argument("--older-than")
+    .argument("2999-01-01")
+    .file("project/package.json", "")
+    .file("project/node_modules/foo/index.js", &"a".repeat(500))
+    .exists(&["project/package.json"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Node project (0 seconds ago)
+        └─ node_modules (500 bytes)
+      Projects cleaned: 1, Bytes deleted: 500 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn newer_than_filters_old_projects() -> Result {
+  Test::new()?
+    .argument("--newer-than")
+    .argument("7d")
+    .age(Duration::from_hours(24 * 30))
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .exists(&["project/Cargo.toml", "project/target/debug/app"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn newer_than_includes_recent_projects() -> Result {
+  Test::new()?
+    .argument("--newer-than")
+    .argument("1w3d")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .exists(&["project/Cargo.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (1000 bytes)
+      Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn rule_older_than_overrides_flag() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("--older-than")
+    .argument("7d")
+    .age(Duration::from_hours(24 * 3))
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "out" }]
+        older_than = "1d"
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/package.json", "")
+    .file("project/node_modules/foo/index.js", &"a".repeat(500))
+    .file("project/out/app", &"a".repeat(100))
+    .exists(&[
+      "project/Makefile",
+      "project/node_modules/foo/index.js",
+      "project/package.json",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (3 days ago)
+        └─ out (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
       "
     })
     .run()
 }
 
+#[test]
+fn select_requires_a_terminal() -> Result {
+  Test::new()?
+    .argument("--select")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", "")
+    .exists(&["project/Cargo.toml", "project/target/debug/app"])
+    .expected_status(1)
+    .expected_stderr("error: `--select` needs an interactive terminal\n")
+    .run()
+}
+
 #[test]
 fn invalid_path_error() -> Result {
   Test::new()?
@@ -915,3 +1165,1533 @@ fn file_path_instead_of_directory_error() -> Result {
     )
     .run()
 }
+
+#[test]
+fn discovers_deeply_nested_projects() -> Result {
+  Test::new()?
+    .file("org/team/repo/Cargo.toml", "")
+    .file("org/team/repo/target/debug/app", &"a".repeat(1000))
+    .exists(&["org/team/repo/Cargo.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/org/team/repo Cargo project (0 seconds ago)
+        └─ target (1000 bytes)
+      Projects cleaned: 1, Bytes deleted: 1000 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn max_depth_limits_discovery() -> Result {
+  Test::new()?
+    .argument("--max-depth")
+    .argument("2")
+    .file("org/team/repo/Cargo.toml", "")
+    .file("org/team/repo/target/debug/app", &"a".repeat(1000))
+    .file("org/web/package.json", "")
+    .file("org/web/node_modules/lodash/index.js", &"b".repeat(500))
+    .exists(&[
+      "org/team/repo/Cargo.toml",
+      "org/team/repo/target/debug/app",
+      "org/web/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/org/web Node project (0 seconds ago)
+        └─ node_modules (500 bytes)
+      Projects cleaned: 1, Bytes deleted: 500 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn nested_projects_are_skipped_by_default() -> Result {
+  Test::new()?
+    .argument("--dry-run")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .file("project/web/package.json", "")
+    .file("project/web/node_modules/lodash/index.js", &"b".repeat(500))
+    .exists(&[
+      "project/Cargo.toml",
+      "project/target/debug/app",
+      "project/web/package.json",
+      "project/web/node_modules/lodash/index.js",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (1000 bytes)
+      Projects matched: 1, Bytes matched: 1000 bytes
+      "
+    })
+    .run()
+}
+
+#[test]
+fn nested_discovers_projects_inside_projects() -> Result {
+  Test::new()?
+    .argument("--nested")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .file("project/web/package.json", "")
+    .file("project/web/node_modules/lodash/index.js", &"b".repeat(500))
+    .exists(&["project/Cargo.toml", "project/web/package.json"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (1000 bytes)
+      [ROOT]/project/web Node project (0 seconds ago)
+        └─ node_modules (500 bytes)
+      Projects cleaned: 2, Bytes deleted: 1.46 KiB
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn detection_ignores_files_inside_cleanup_targets() -> Result {
+  Test::new()?
+    .argument("--dry-run")
+    .file("tools/bin/vendor/Vendor.csproj", "")
+    .file("tools/bin/vendor/Vendor.dll", &"a".repeat(1000))
+    .exists(&[
+      "tools/bin/vendor/Vendor.csproj",
+      "tools/bin/vendor/Vendor.dll",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      Projects matched: 0, Bytes matched: 0 bytes
+      "
+    })
+    .run()
+}
+
+#[test]
+fn jobs_output_is_deterministic() -> Result {
+  Test::new()?
+    .argument("--jobs")
+    .argument("4")
+    .file("a/package.json", "")
+    .file("a/node_modules/lodash/index.js", &"a".repeat(1000))
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"b".repeat(500))
+    .file("c/pyproject.toml", "")
+    .file("c/.venv/bin/python", &"c".repeat(300))
+    .exists(&["a/package.json", "b/Cargo.toml", "c/pyproject.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/a Node project (0 seconds ago)
+        └─ node_modules (1000 bytes)
+      [ROOT]/b Cargo project (0 seconds ago)
+        └─ target (500 bytes)
+      [ROOT]/c Python project (0 seconds ago)
+        └─ .venv (300 bytes)
+      Projects cleaned: 3, Bytes deleted: 1.76 KiB
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn json_format() -> Result {
+  Test::new()?
+    .argument("--dry-run")
+    .argument("--format")
+    .argument("json")
+    .mtime(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .exists(&["project/Cargo.toml", "project/target/debug/app"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      r#"
+      {
+        "projects": [
+          {
+            "modified": 1700000000,
+            "root": "[ROOT]/project",
+            "rule_id": "cargo",
+            "rule_name": "Cargo",
+            "skip": null,
+            "skipped": [],
+            "tasks": [
+              {
+                "type": "remove",
+                "path": "target",
+                "size": 1000
+              }
+            ]
+          }
+        ],
+        "summary": {
+          "bytes": 1000,
+          "dry_run": true,
+          "projects": 1,
+          "trash": false
+        }
+      }
+      "#
+    })
+    .run()
+}
+
+#[test]
+fn ndjson_format() -> Result {
+  Test::new()?
+    .argument("--format")
+    .argument("ndjson")
+    .mtime(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    .file("a/package.json", "")
+    .file("a/node_modules/lodash/index.js", &"a".repeat(1000))
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"b".repeat(500))
+    .exists(&["a/package.json", "b/Cargo.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      r#"
+      {"type":"project","modified":1700000000,"root":"[ROOT]/a","rule_id":"node","rule_name":"Node","skip":null,"skipped":[],"tasks":[{"type":"remove","path":"node_modules","size":1000}]}
+      {"type":"project","modified":1700000000,"root":"[ROOT]/b","rule_id":"cargo","rule_name":"Cargo","skip":null,"skipped":[],"tasks":[{"type":"remove","path":"target","size":500}]}
+      {"type":"summary","bytes":1500,"dry_run":false,"projects":2,"reclaimed":[SIZE],"trash":false}
+      "#
+    })
+    .run()
+}
+
+#[test]
+fn streamed_output_goes_to_stderr() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("--format")
+    .argument("ndjson")
+    .mtime(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "echo hello", output = "stream" }]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      r#"
+      {"type":"project","modified":1700000000,"root":"[ROOT]/project","rule_id":"make","rule_name":"make","skip":null,"skipped":[],"tasks":[{"type":"command","command":"echo hello"}]}
+      {"type":"summary","bytes":0,"dry_run":false,"projects":1,"reclaimed":[SIZE],"trash":false}
+      "#
+    })
+    .expected_stderr("hello\n")
+    .run()
+}
+
+#[test]
+fn trash_moves_paths_instead_of_deleting() -> Result {
+  Test::new()?
+    .argument("--trash")
+    .env("XDG_DATA_HOME", "[ROOT]/data")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .exists(&["project/Cargo.toml", "data/swab/trash/manifest.jsonl"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (1000 bytes)
+      Projects cleaned: 1, Bytes trashed: 1000 bytes
+      "
+    })
+    .run()
+}
+
+#[test]
+fn tracked_paths_are_skipped() -> Result {
+  Test::new()?
+    .file("project/build.gradle", "")
+    .file("project/.gradle/cache", &"a".repeat(100))
+    .file("project/build/generated.java", "class Generated {}")
+    .tracked(&["project/build.gradle", "project/build/generated.java"])
+    .exists(&["project/build.gradle", "project/build/generated.java"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Gradle project (0 seconds ago)
+        ├─ .gradle (100 bytes)
+        └─ build (skipped: tracked by git)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn allow_tracked_removes_tracked_paths() -> Result {
+  Test::new()?
+    .argument("--allow-tracked")
+    .file("project/build.gradle", "")
+    .file("project/build/generated.java", "class Generated {}")
+    .tracked(&["project/build.gradle", "project/build/generated.java"])
+    .exists(&["project/build.gradle"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Gradle project (0 seconds ago)
+        └─ build (18 bytes)
+      Projects cleaned: 1, Bytes deleted: 18 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn skip_dirty_skips_projects_with_uncommitted_changes() -> Result {
+  Test::new()?
+    .argument("--skip-dirty")
+    .file("clean/Cargo.toml", "")
+    .file("clean/target/debug/app", &"a".repeat(100))
+    .file("dirty/Cargo.toml", "")
+    .file("dirty/src/main.rs", "fn main() {}")
+    .file("dirty/target/debug/app", &"b".repeat(100))
+    .tracked(&["clean/Cargo.toml", "dirty/Cargo.toml"])
+    .exists(&["clean/Cargo.toml", "dirty/Cargo.toml", "dirty/src/main.rs"])
+    .exists(&["dirty/target/debug/app"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/clean Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      [ROOT]/dirty Cargo project (0 seconds ago) (skipped: has uncommitted changes)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn skip_dirty_skips_projects_git_cannot_check() -> Result {
+  Test::new()?
+    .argument("--skip-dirty")
+    .file("broken/.git/HEAD", "")
+    .file("broken/Cargo.toml", "")
+    .file("broken/target/debug/app", &"a".repeat(100))
+    .file("other/Cargo.toml", "")
+    .file("other/target/debug/app", &"b".repeat(100))
+    .exists(&[
+      "broken/.git/HEAD",
+      "broken/Cargo.toml",
+      "broken/target/debug/app",
+      "other/Cargo.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/broken Cargo project (0 seconds ago) (skipped: couldn't check for uncommitted changes: `git status` failed in `[ROOT]/broken`: fatal: not a git repository (or any of the parent directories): .git)
+        └─ target (100 bytes)
+      [ROOT]/other Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn local_config_keeps_and_removes_paths() -> Result {
+  Test::new()?
+    .file("project/package.json", "")
+    .file("project/node_modules/lodash/index.js", &"a".repeat(100))
+    .file("project/generated/schema.rs", &"b".repeat(50))
+    .file(
+      "project/.swab.toml",
+      "keep = ['node_modules']\nremove = ['generated']\n",
+    )
+    .exists(&[
+      "project/.swab.toml",
+      "project/node_modules/lodash/index.js",
+      "project/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Node project (0 seconds ago)
+        └─ node_modules (skipped: matches a keep pattern)
+      [ROOT]/project Local project (0 seconds ago)
+        └─ generated (50 bytes)
+      Projects cleaned: 1, Bytes deleted: 50 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn local_config_disables_rules() -> Result {
+  Test::new()?
+    .file("project/Cargo.toml", "")
+    .file("project/package.json", "")
+    .file("project/node_modules/lodash/index.js", "")
+    .file("project/target/debug/app", &"a".repeat(100))
+    .file("project/.swab.toml", "disabled = ['node']\n")
+    .exists(&[
+      "project/.swab.toml",
+      "project/Cargo.toml",
+      "project/node_modules/lodash/index.js",
+      "project/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn local_config_opts_project_out() -> Result {
+  Test::new()?
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(100))
+    .file("project/web/package.json", "")
+    .file("project/web/node_modules/lodash/index.js", "")
+    .file("project/.swab.toml", "skip = true\n")
+    .exists(&[
+      "project/.swab.toml",
+      "project/Cargo.toml",
+      "project/target/debug/app",
+      "project/web/node_modules/lodash/index.js",
+      "project/web/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago) (skipped: opted out by .swab.toml)
+        └─ target (100 bytes)
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn invalid_local_config_skips_project() -> Result {
+  Test::new()?
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(100))
+    .file("project/.swab.toml", "kep = ['target']\n")
+    .file("other/Cargo.toml", "")
+    .file("other/target/debug/app", &"a".repeat(100))
+    .exists(&[
+      "other/Cargo.toml",
+      "project/.swab.toml",
+      "project/Cargo.toml",
+      "project/target/debug/app",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/other Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      [ROOT]/project Cargo project (0 seconds ago) (skipped: invalid .swab.toml: unknown field `kep`, expected one of `disabled`, `keep`, `remove`, `skip`)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn config_validate_points_at_invalid_fields() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("config")
+    .argument("validate")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        exclude = ["bad["]
+
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "build[" }]
+      "#},
+    )
+    .exists(&["swab.toml"])
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      swab.toml:1:12: invalid exclude pattern `bad[`: error parsing glob 'bad[': unclosed character class; missing ']'
+      swab.toml:6:12: invalid remove pattern `build[`: error parsing glob 'build[': unclosed character class; missing ']'
+      error: config file `swab.toml` is invalid
+      "
+    })
+    .run()
+}
+
+#[test]
+fn config_validate_accepts_valid_config() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("config")
+    .argument("validate")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "build" }]
+      "#},
+    )
+    .exists(&["swab.toml"])
+    .expected_stdout("swab.toml is valid\n")
+    .run()
+}
+
+#[test]
+fn config_flag_loads_alternate_config() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      "[[rules]]\nid = \"make\"\ndetection = \"Makefile\"\nactions = [{ remove = \"out\" }]\n",
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", &"a".repeat(100))
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ out (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn config_environment_variable_loads_alternate_config() -> Result {
+  Test::new()?
+    .env("SWAB_CONFIG", "[ROOT]/swab.toml")
+    .file("swab.toml", "[default]\ndisabled = [\"cargo\"]\n")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", "")
+    .exists(&[
+      "project/Cargo.toml",
+      "project/target/debug/app",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn no_config_ignores_config_file() -> Result {
+  Test::new()?
+    .argument("--no-config")
+    .env("SWAB_CONFIG", "[ROOT]/swab.toml")
+    .file("swab.toml", "[default]\ndisabled = [\"cargo\"]\n")
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(100))
+    .exists(&["project/Cargo.toml", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn rules_shows_custom_overrides_of_builtin_rules() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("rules")
+    .argument("--tag")
+    .argument("heavy")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "cargo"
+        detection = "Cargo.toml"
+        tags = ["heavy"]
+        actions = [{ remove = "target/debug" }]
+      "#},
+    )
+    .exists(&["swab.toml"])
+    .expected_stdout(indoc! {
+      "
+      cargo (cargo) [custom]
+        detection: Cargo.toml
+        tags: heavy
+        actions:
+          remove target/debug
+      "
+    })
+    .run()
+}
+
+#[test]
+fn no_config_conflicts_with_config() -> Result {
+  Test::new()?
+    .argument("--no-config")
+    .argument("--config")
+    .argument("swab.toml")
+    .expected_status(2)
+    .expected_stderr(indoc! {
+      "
+      error: the argument '--no-config' cannot be used with '--config <PATH>'
+
+      Usage: swab --apparent-size --no-config <DIRECTORIES>...
+
+      For more information, try '--help'.
+      "
+    })
+    .run()
+}
+
+#[test]
+fn missing_config_file_error() -> Result {
+  Test::new()?
+    .env("SWAB_CONFIG", "[ROOT]/missing.toml")
+    .expected_status(1)
+    .expected_stderr(
+      "error: failed to read config file `[ROOT]/missing.toml`: No such file or directory (os error 2)\n",
+    )
+    .run()
+}
+
+#[test]
+fn custom_rules_can_match_file_contents() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "sphinx"
+        detection = { file = "Makefile", contains = "SPHINXBUILD\\s*\\?=" }
+        actions = [{ remove = "_build" }]
+      "#},
+    )
+    .file("docs/Makefile", "SPHINXBUILD ?= sphinx-build\n")
+    .file("docs/_build/index.html", &"a".repeat(100))
+    .file("app/Makefile", "all:\n")
+    .file("app/_build/app", &"a".repeat(100))
+    .exists(&[
+      "app/Makefile",
+      "app/_build/app",
+      "docs/Makefile",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/docs sphinx project (0 seconds ago)
+        └─ _build (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn custom_rules_can_match_manifest_keys() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "workspace"
+        detection = { file = "Cargo.toml", key = "workspace.members" }
+        actions = [{ remove = "dist" }]
+      "#},
+    )
+    .file("root/Cargo.toml", "[workspace]\nmembers = []\n")
+    .file("root/dist/app", &"a".repeat(100))
+    .file("member/Cargo.toml", "[package]\nname = \"member\"\n")
+    .file("member/dist/app", &"a".repeat(100))
+    .exists(&[
+      "member/Cargo.toml",
+      "member/dist/app",
+      "root/Cargo.toml",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/root workspace project (0 seconds ago)
+        └─ dist (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn custom_rules_accept_detection_expressions() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "dotnet-app"
+        detection = "(*.csproj | *.fsproj) & !project.godot"
+        actions = [{ remove = "out" }]
+      "#},
+    )
+    .file("app/App.fsproj", "")
+    .file("app/out/App.dll", &"a".repeat(100))
+    .file("game/Game.csproj", "")
+    .file("game/project.godot", "")
+    .file("game/out/Game.dll", &"a".repeat(100))
+    .exists(&[
+      "app/App.fsproj",
+      "game/Game.csproj",
+      "game/out/Game.dll",
+      "game/project.godot",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app dotnet-app project (0 seconds ago)
+        └─ out (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn invalid_detection_expression_error() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "broken"
+        detection = "(*.csproj | *.fsproj"
+        actions = [{ remove = "out" }]
+      "#},
+    )
+    .exists(&["swab.toml"])
+    .expected_status(1)
+    .expected_stderr(
+      "error: invalid detection expression `(*.csproj | *.fsproj`: unclosed `(` at column 1\n",
+    )
+    .run()
+}
+
+#[test]
+fn rule_keep_patterns_are_respected() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "**/out" }]
+        keep = ["packages/foo/out"]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", &"a".repeat(100))
+    .file("project/packages/foo/out/lib", &"b".repeat(50))
+    .exists(&[
+      "project/Makefile",
+      "project/packages/foo/out/lib",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        ├─ out (100 bytes)
+        └─ packages/foo/out (skipped: matches a keep pattern)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn invalid_keep_pattern_error() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "out" }]
+        keep = ["out["]
+      "#},
+    )
+    .exists(&["swab.toml"])
+    .expected_status(1)
+    .expected_stderr(
+      "error: invalid keep pattern `out[`: error parsing glob 'out[': unclosed character class; missing ']'\n",
+    )
+    .run()
+}
+
+#[test]
+fn exclude_skips_matching_paths() -> Result {
+  Test::new()?
+    .argument("--dry-run")
+    .argument("--exclude")
+    .argument("packages/foo/node_modules")
+    .file("project/package.json", "")
+    .file("project/node_modules/lodash/index.js", &"a".repeat(100))
+    .file(
+      "project/packages/foo/node_modules/x/index.js",
+      &"b".repeat(50),
+    )
+    .exists(&[
+      "project/node_modules/lodash/index.js",
+      "project/package.json",
+      "project/packages/foo/node_modules/x/index.js",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Node project (0 seconds ago)
+        ├─ node_modules (100 bytes)
+        └─ packages/foo/node_modules (skipped: excluded)
+      Projects matched: 1, Bytes matched: 100 bytes
+      "
+    })
+    .run()
+}
+
+#[test]
+fn exclude_skips_removals_containing_excluded_paths() -> Result {
+  Test::new()?
+    .argument("--exclude")
+    .argument("vendor/foo")
+    .file("app/composer.json", "")
+    .file("app/vendor/bar/lib.php", &"a".repeat(100))
+    .file("app/vendor/foo/lib.php", &"b".repeat(100))
+    .exists(&[
+      "app/composer.json",
+      "app/vendor/bar/lib.php",
+      "app/vendor/foo/lib.php",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app Composer (PHP) project (0 seconds ago)
+        └─ vendor (skipped: contains an excluded path)
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn exclude_skips_whole_projects() -> Result {
+  Test::new()?
+    .argument("--exclude")
+    .argument("critical")
+    .file("app/Cargo.toml", "")
+    .file("app/target/debug/app", &"a".repeat(100))
+    .file("critical/Cargo.toml", "")
+    .file("critical/target/debug/app", &"b".repeat(100))
+    .exists(&[
+      "app/Cargo.toml",
+      "critical/Cargo.toml",
+      "critical/target/debug/app",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      [ROOT]/critical Cargo project (0 seconds ago) (skipped: excluded)
+        └─ target (skipped: excluded)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn config_exclude_skips_matching_paths() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file("swab.toml", "exclude = [\"legacy\"]\n")
+    .file("app/Cargo.toml", "")
+    .file("app/target/debug/app", &"a".repeat(100))
+    .file("legacy/Cargo.toml", "")
+    .file("legacy/target/debug/app", &"b".repeat(100))
+    .exists(&[
+      "app/Cargo.toml",
+      "legacy/Cargo.toml",
+      "legacy/target/debug/app",
+      "swab.toml",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      [ROOT]/legacy Cargo project (0 seconds ago) (skipped: excluded)
+        └─ target (skipped: excluded)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn rule_flag_selects_rules() -> Result {
+  Test::new()?
+    .argument("--rule")
+    .argument("cargo")
+    .file("app/Cargo.toml", "")
+    .file("app/target/debug/app", &"a".repeat(100))
+    .file("web/package.json", "")
+    .file("web/node_modules/lodash/index.js", &"b".repeat(100))
+    .exists(&[
+      "app/Cargo.toml",
+      "web/node_modules/lodash/index.js",
+      "web/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn skip_tag_flag_skips_tagged_rules() -> Result {
+  Test::new()?
+    .argument("--skip-tag")
+    .argument("js")
+    .file("app/Cargo.toml", "")
+    .file("app/target/debug/app", &"a".repeat(100))
+    .file("web/package.json", "")
+    .file("web/node_modules/lodash/index.js", &"b".repeat(100))
+    .exists(&[
+      "app/Cargo.toml",
+      "web/node_modules/lodash/index.js",
+      "web/package.json",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/app Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn tag_flag_selects_custom_rules() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("--tag")
+    .argument("c")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "out" }]
+        tags = ["c"]
+      "#},
+    )
+    .file("app/Cargo.toml", "")
+    .file("app/target/debug/app", &"a".repeat(100))
+    .file("tool/Makefile", "")
+    .file("tool/out/tool", &"b".repeat(100))
+    .exists(&["app/Cargo.toml", "app/target/debug/app", "swab.toml"])
+    .exists(&["tool/Makefile"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/tool make project (0 seconds ago)
+        └─ out (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn unknown_rule_error() -> Result {
+  Test::new()?
+    .argument("--rule")
+    .argument("carg")
+    .expected_status(1)
+    .expected_stderr("error: no enabled rule with id `carg`\n")
+    .run()
+}
+
+#[test]
+fn min_size_skips_small_paths() -> Result {
+  Test::new()?
+    .argument("--min-size")
+    .argument("1 KiB")
+    .file("project/pyproject.toml", "")
+    .file("project/__pycache__/a.pyc", &"a".repeat(10))
+    .file("project/.venv/lib/a.py", &"b".repeat(2048))
+    .exists(&["project/__pycache__/a.pyc", "project/pyproject.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project Python project (0 seconds ago)
+        ├─ .venv (2 KiB)
+        └─ __pycache__ (skipped: below the minimum size)
+      Projects cleaned: 1, Bytes deleted: 2 KiB
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn command_output_is_captured() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "echo cleaning; rm -r out" }]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", "")
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ run echo cleaning; rm -r out
+      Projects cleaned: 1, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn command_output_is_printed_with_verbose() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("--verbose")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "echo $GREETING from $(basename $PWD)", cwd = "out", env = { GREETING = "hello" } }]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", "")
+    .exists(&["project/Makefile", "project/out/app", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ run echo $GREETING from $(basename $PWD)
+      Projects cleaned: 1, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .expected_stderr("hello from out\n")
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn command_failure_shows_output() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "echo no rule to make clean; exit 2" }]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(1)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ run echo no rule to make clean; exit 2
+      "
+    })
+    .expected_stderr(indoc! {
+      "
+      error: command `echo no rule to make clean; exit 2` failed in `[ROOT]/project` with exit status: 2:
+      no rule to make clean
+      "
+    })
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn command_timeout() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "sleep 30", timeout = "1s" }]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(1)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ run sleep 30
+      "
+    })
+    .expected_stderr(
+      "error: command `sleep 30` timed out after 1s in `[ROOT]/project`\n",
+    )
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn tool_command_runs_instead_of_removals() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [
+          { command = "rm -r out/debug", tool = "sh" },
+          { remove = "out" },
+        ]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/debug/app", "")
+    .file("project/out/release/app", "")
+    .exists(&["project/Makefile", "project/out/release/app", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ run rm -r out/debug
+      Projects cleaned: 1, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn missing_tool_falls_back_to_removals() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [
+          { command = "swab-missing-tool clean", tool = "swab-missing-tool" },
+          { remove = "out" },
+        ]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", &"a".repeat(100))
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago) (`swab-missing-tool` not found, removing instead)
+        └─ out (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn missing_tool_is_reported_without_removals() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [
+          { command = "swab-missing-tool clean", tool = "swab-missing-tool" },
+          { remove = "out" },
+        ]
+      "#},
+    )
+    .file("project/Makefile", "")
+    .exists(&["project/Makefile", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago) (`swab-missing-tool` not found, nothing to remove instead)
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn command_failure_stops_later_projects() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "exit 2" }]
+      "#},
+    )
+    .file("a/Makefile", "")
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"a".repeat(100))
+    .exists(&[
+      "a/Makefile",
+      "b/Cargo.toml",
+      "b/target/debug/app",
+      "swab.toml",
+    ])
+    .expected_status(1)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/a make project (0 seconds ago)
+        └─ run exit 2
+      "
+    })
+    .expected_stderr(
+      "error: command `exit 2` failed in `[ROOT]/a` with exit status: 2\n",
+    )
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn keep_going_reports_failures_at_the_end() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .argument("--keep-going")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ command = "echo missing target; exit 2" }]
+      "#},
+    )
+    .file("a/Makefile", "")
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"a".repeat(100))
+    .exists(&["a/Makefile", "b/Cargo.toml", "swab.toml"])
+    .expected_status(1)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/a make project (0 seconds ago)
+        └─ run echo missing target; exit 2
+      [ROOT]/b Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      Failures: 1
+      [ROOT]/a (make)
+        error: command `echo missing target; exit 2` failed in `[ROOT]/a` with exit status: 2:
+          missing target
+      "
+    })
+    .expected_stderr("error: 1 task failed\n")
+    .run()
+}
+
+#[test]
+#[cfg(unix)]
+fn hard_links_are_credited_to_the_first_project() -> Result {
+  let content = "x".repeat(1 << 20);
+
+  Test::new()?
+    .allocated()
+    .argument("--dry-run")
+    .argument("--min-size")
+    .argument("512KiB")
+    .file("a/Cargo.toml", "")
+    .file("a/target/blob", &content)
+    .file("b/Cargo.toml", "")
+    .link("a/target/blob", "b/target/blob")
+    .exists(&[
+      "a/Cargo.toml",
+      "a/target/blob",
+      "b/Cargo.toml",
+      "b/target/blob",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/a Cargo project (0 seconds ago)
+        └─ target (1 MiB)
+      [ROOT]/b Cargo project (0 seconds ago)
+        └─ target (skipped: below the minimum size)
+      Projects matched: 1, Bytes matched: 1 MiB
+      "
+    })
+    .run()
+}
+
+#[test]
+fn rule_min_size_overrides_flag() -> Result {
+  Test::new()?
+    .argument("--config")
+    .argument("swab.toml")
+    .file(
+      "swab.toml",
+      indoc! {r#"
+        [[rules]]
+        id = "make"
+        detection = "Makefile"
+        actions = [{ remove = "out" }]
+        min_size = "1 KiB"
+      "#},
+    )
+    .file("project/Makefile", "")
+    .file("project/out/app", &"a".repeat(100))
+    .exists(&["project/Makefile", "project/out/app", "swab.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/project make project (0 seconds ago)
+        └─ out (skipped: below the minimum size)
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sort_size_orders_largest_first() -> Result {
+  Test::new()?
+    .argument("--dry-run")
+    .argument("--sort")
+    .argument("size")
+    .file("a/Cargo.toml", "")
+    .file("a/target/debug/app", &"a".repeat(10))
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"b".repeat(100))
+    .exists(&[
+      "a/Cargo.toml",
+      "a/target/debug/app",
+      "b/Cargo.toml",
+      "b/target/debug/app",
+    ])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/b Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      [ROOT]/a Cargo project (0 seconds ago)
+        └─ target (10 bytes)
+      Projects matched: 2, Bytes matched: 110 bytes
+      "
+    })
+    .run()
+}
+
+#[test]
+fn top_cleans_only_the_largest_projects() -> Result {
+  Test::new()?
+    .argument("--top")
+    .argument("1")
+    .file("a/Cargo.toml", "")
+    .file("a/target/debug/app", &"a".repeat(10))
+    .file("b/Cargo.toml", "")
+    .file("b/target/debug/app", &"b".repeat(100))
+    .exists(&["a/Cargo.toml", "a/target/debug/app", "b/Cargo.toml"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      [ROOT]/b Cargo project (0 seconds ago)
+        └─ target (100 bytes)
+      Projects cleaned: 1, Bytes deleted: 100 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}
+
+#[test]
+fn free_does_nothing_when_enough_space_is_available() -> Result {
+  Test::new()?
+    .argument("--free")
+    .argument("0")
+    .argument("--format")
+    .argument("ndjson")
+    .mtime(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(100))
+    .exists(&["project/Cargo.toml", "project/target/debug/app"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      r#"
+      {"type":"plan","available":[SIZE],"freed":0,"needed":0,"projects":0,"root":"[ROOT]"}
+      {"type":"project","modified":1700000000,"root":"[ROOT]/project","rule_id":"cargo","rule_name":"Cargo","skip":"unneeded","skipped":[],"tasks":[{"type":"remove","path":"target","size":100}]}
+      {"type":"summary","bytes":0,"dry_run":false,"projects":0,"reclaimed":[SIZE],"trash":false}
+      "#
+    })
+    .run()
+}
+
+#[test]
+fn free_cleans_projects_until_the_target_is_reached() -> Result {
+  Test::new()?
+    .argument("--free")
+    .argument("1000000EiB")
+    .argument("--quiet")
+    .file("a/Cargo.toml", "")
+    .file("a/target/debug/app", &"a".repeat(100))
+    .file("b/package.json", "")
+    .file("b/node_modules/lodash/index.js", &"b".repeat(100))
+    .exists(&["a/Cargo.toml", "b/package.json"])
+    .expected_status(0)
+    .run()
+}
+
+#[test]
+fn age_source_commit_uses_last_commit_time() -> Result {
+  Test::new()?
+    .argument("--older-than")
+    .argument("7d")
+    .argument("--age-source")
+    .argument("commit")
+    .age(Duration::from_hours(24 * 30))
+    .file("project/Cargo.toml", "")
+    .file("project/target/debug/app", &"a".repeat(1000))
+    .tracked(&["project/Cargo.toml"])
+    .exists(&["project/Cargo.toml", "project/target/debug/app"])
+    .expected_status(0)
+    .expected_stdout(indoc! {
+      "
+      Projects cleaned: 0, Bytes deleted: 0 bytes
+      Space reclaimed: [SIZE]
+      "
+    })
+    .run()
+}